@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anyhow::{anyhow, bail, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::from_account, clock::Clock, instruction::Instruction, pubkey::Pubkey,
+    signature::Keypair, signer::Signer, sysvar, transaction::Transaction,
+};
+use tool_lp_client::{fetch_all_vaults, fetch_held_user_locks, fetch_pool_user_locks, pda};
+
+use crate::{
+    output::{print, FeePreviewView, LockView, LocksView, TransactionView},
+    CliContext,
+};
+
+pub fn init_vault(context: &CliContext, pool: &Pubkey) -> Result<()> {
+    let signer = context.signer()?;
+    let ix = tool_lp_client::initialize_vault(&context.rpc, &signer.pubkey(), pool)?;
+    send(context, &signer, vec![ix])
+}
+
+pub fn deposit(
+    context: &CliContext,
+    pool: &Pubkey,
+    amount: u64,
+    unlock_timestamp: Option<i64>,
+    duration: Option<i64>,
+    beneficiary: Option<Pubkey>,
+) -> Result<()> {
+    let signer = context.signer()?;
+    let unlock_timestamp = match (unlock_timestamp, duration) {
+        (Some(unlock_timestamp), _) => unlock_timestamp,
+        (None, Some(duration)) => cluster_time(&context.rpc)? + duration,
+        (None, None) => bail!("either --unlock-timestamp or --duration is required"),
+    };
+    let ix = tool_lp_client::deposit(
+        &context.rpc,
+        &signer.pubkey(),
+        &signer.pubkey(),
+        &beneficiary.unwrap_or(signer.pubkey()),
+        pool,
+        amount,
+        unlock_timestamp,
+    )?;
+    send(context, &signer, vec![ix])
+}
+
+pub fn withdraw(
+    context: &CliContext,
+    pool: &Pubkey,
+    amount: Option<u64>,
+    user: Option<Pubkey>,
+) -> Result<()> {
+    let signer = context.signer()?;
+    let user = user.unwrap_or(signer.pubkey());
+    let (vault, _) = pda::vault(pool);
+    let (user_lock, _) = pda::user_lock(&vault, &user);
+    let lock = tool_lp_client::fetch_user_lock(&context.rpc, &user_lock)?;
+
+    // Proceeds go to the owner's associated accounts, which must exist. A
+    // tokenized lock pays whoever holds its position NFT.
+    let owner = tool_lp_client::fetch_lock_owner(&context.rpc, &lock)?;
+    let pool_state = tool_lp_client::fetch_pool_state(&context.rpc, pool)?;
+    let (token_0_mint, token_0_program) = (pool_state.token_0_mint, pool_state.token_0_program);
+    let (token_1_mint, token_1_program) = (pool_state.token_1_mint, pool_state.token_1_program);
+    let mut ixs = vec![
+        create_associated_token_account_idempotent(
+            &signer.pubkey(),
+            &owner,
+            &token_0_mint,
+            &token_0_program,
+        ),
+        create_associated_token_account_idempotent(
+            &signer.pubkey(),
+            &owner,
+            &token_1_mint,
+            &token_1_program,
+        ),
+    ];
+    ixs.push(tool_lp_client::withdraw(
+        &context.rpc,
+        &signer.pubkey(),
+        &signer.pubkey(),
+        pool,
+        &user_lock,
+        amount.unwrap_or(lock.amount),
+    )?);
+    send(context, &signer, ixs)
+}
+
+pub fn locks(context: &CliContext, user: Option<Pubkey>) -> Result<()> {
+    let user = context.key_or_signer(user)?;
+    let current_timestamp = cluster_time(&context.rpc)?;
+
+    let pools: HashMap<Pubkey, Pubkey> = fetch_all_vaults(&context.rpc)?
+        .into_iter()
+        .map(|(vault, account)| (vault, account.pool_state))
+        .collect();
+    let locks = fetch_held_user_locks(&context.rpc, &user)?
+        .into_iter()
+        .filter_map(|(address, lock)| {
+            let pool_state = pools.get(&lock.vault)?;
+            Some(LockView::new(
+                address,
+                lock.vault,
+                *pool_state,
+                &lock,
+                current_timestamp,
+            ))
+        })
+        .collect();
+
+    print(context.output, &LocksView(locks))
+}
+
+pub fn pool_locks(context: &CliContext, pool: &Pubkey) -> Result<()> {
+    let (vault, _) = pda::vault(pool);
+    let current_timestamp = cluster_time(&context.rpc)?;
+    let mut locks: Vec<LockView> = fetch_pool_user_locks(&context.rpc, pool)?
+        .into_iter()
+        .map(|(address, lock)| LockView::new(address, vault, *pool, &lock, current_timestamp))
+        .collect();
+    locks.sort_by_key(|lock| lock.unlock_timestamp);
+
+    print(context.output, &LocksView(locks))
+}
+
+pub fn fee_preview(
+    context: &CliContext,
+    pool: &Pubkey,
+    user: Option<Pubkey>,
+    amount: Option<u64>,
+) -> Result<()> {
+    let user = context.key_or_signer(user)?;
+    let (vault_key, vault) = tool_lp_client::fetch_vault_for_pool(&context.rpc, pool)?;
+    let (user_lock, _) = pda::user_lock(&vault_key, &user);
+    let lp_amount = match amount {
+        Some(amount) => amount,
+        None => tool_lp_client::fetch_user_lock(&context.rpc, &user_lock)?.amount,
+    };
+    // The simulation only needs an existing fee payer; fall back to the user
+    // when no keypair is configured.
+    let payer = context.signer().map_or(user, |signer| signer.pubkey());
+    let quote = tool_lp_client::quote_withdraw(&context.rpc, &payer, pool, &user_lock, lp_amount)?;
+
+    print(context.output, &FeePreviewView::new(&quote, vault.fee_bps))
+}
+
+fn send(context: &CliContext, signer: &Keypair, ixs: Vec<Instruction>) -> Result<()> {
+    let blockhash = context.rpc.get_latest_blockhash()?;
+    let transaction =
+        Transaction::new_signed_with_payer(&ixs, Some(&signer.pubkey()), &[signer], blockhash);
+    let signature = context.rpc.send_and_confirm_transaction(&transaction)?;
+    print(context.output, &TransactionView { signature })
+}
+
+/// Cluster time rather than the local clock, which can differ on a test validator.
+fn cluster_time(rpc: &RpcClient) -> Result<i64> {
+    let clock: Clock = from_account(&rpc.get_account(&sysvar::clock::ID)?)
+        .ok_or_else(|| anyhow!("failed to decode the clock sysvar"))?;
+    Ok(clock.unix_timestamp)
+}
@@ -0,0 +1,215 @@
+//! `tool-lp`: manage vaults and LP locks from the command line. The RPC URL and
+//! keypair default to the Solana CLI config.
+//!
+//! # Local validator
+//!
+//! A `solana-test-validator` needs the program, Raydium's cp-swap program and a
+//! pool to lock LP of. Build with the `devnet` feature so the program calls
+//! Raydium's devnet deployment, and clone that deployment and a devnet pool
+//! from devnet. The program config can only be created by `ADMIN_WALLET`, so it
+//! is cloned as well:
+//!
+//! ```text
+//! anchor build -- --features devnet
+//! solana find-program-derived-address DduTe3VFPwWGN2EBh8FZ1GSnXe7VFotp1A8eej7qwgX2 string:config
+//! solana-test-validator --reset --url devnet \
+//!     --bpf-program DduTe3VFPwWGN2EBh8FZ1GSnXe7VFotp1A8eej7qwgX2 target/deploy/tool_lp.so \
+//!     --clone-upgradeable-program <raydium cp-swap devnet program> \
+//!     --clone <config> --clone <pool state> --clone <amm config> \
+//!     --clone <token 0 vault> --clone <token 1 vault> --clone <lp mint> \
+//!     --clone <token 0 mint> --clone <token 1 mint> --clone <observation state>
+//! ```
+//!
+//! The signer then needs LP of the cloned pool, from a Raydium deposit, before
+//! the commands run against it with `-u localhost`:
+//!
+//! ```text
+//! tool-lp -u localhost init-vault --pool <pool state>
+//! tool-lp -u localhost deposit --pool <pool state> --amount 1000 --duration 60
+//! tool-lp -u localhost locks
+//! tool-lp -u localhost fee-preview --pool <pool state>
+//! tool-lp -u localhost withdraw --pool <pool state>
+//! ```
+
+mod commands;
+mod output;
+
+use std::str::FromStr;
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+};
+
+#[derive(Parser)]
+#[command(name = "tool-lp", version, about)]
+struct Cli {
+    /// Solana CLI config file
+    #[arg(long, global = true)]
+    config: Option<String>,
+    /// RPC URL or moniker (localhost, devnet, testnet, mainnet-beta); overrides the config file
+    #[arg(long, short = 'u', global = true)]
+    url: Option<String>,
+    /// Signer keypair; overrides the config file
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<String>,
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Display)]
+    output: OutputFormat,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Display,
+    Json,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the vault for a Raydium pool
+    InitVault {
+        #[arg(long)]
+        pool: Pubkey,
+    },
+    /// Lock LP from the signer's associated LP account
+    Deposit {
+        #[arg(long)]
+        pool: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// Unix timestamp the lock unlocks at
+        #[arg(
+            long,
+            conflicts_with = "duration",
+            required_unless_present = "duration"
+        )]
+        unlock_timestamp: Option<i64>,
+        /// Seconds from now, by cluster time, the lock unlocks after
+        #[arg(long)]
+        duration: Option<i64>,
+        /// Owner of the lock; defaults to the signer
+        #[arg(long)]
+        beneficiary: Option<Pubkey>,
+    },
+    /// Withdraw unlocked LP as pool tokens
+    Withdraw {
+        #[arg(long)]
+        pool: Pubkey,
+        /// LP to withdraw; defaults to the whole lock
+        #[arg(long)]
+        amount: Option<u64>,
+        /// Key the lock is held under; defaults to the signer
+        #[arg(long)]
+        user: Option<Pubkey>,
+    },
+    /// Show the locks a user holds, directly or by position NFT, across all vaults
+    Locks {
+        /// Defaults to the signer
+        #[arg(long)]
+        user: Option<Pubkey>,
+    },
+    /// List every lock in a pool's vault
+    PoolLocks {
+        #[arg(long)]
+        pool: Pubkey,
+    },
+    /// Preview the payout and performance fee of a withdrawal
+    FeePreview {
+        #[arg(long)]
+        pool: Pubkey,
+        /// Key the lock is held under; defaults to the signer
+        #[arg(long)]
+        user: Option<Pubkey>,
+        /// LP to quote; defaults to the whole lock
+        #[arg(long)]
+        amount: Option<u64>,
+    },
+}
+
+pub struct CliContext {
+    pub rpc: RpcClient,
+    keypair_path: String,
+    pub output: OutputFormat,
+}
+
+impl CliContext {
+    pub fn signer(&self) -> Result<Keypair> {
+        read_keypair_file(&self.keypair_path)
+            .map_err(|err| anyhow!("failed to read keypair {}: {err}", self.keypair_path))
+    }
+
+    /// The given key, or the signer's when omitted.
+    pub fn key_or_signer(&self, key: Option<Pubkey>) -> Result<Pubkey> {
+        match key {
+            Some(key) => Ok(key),
+            None => Ok(solana_sdk::signer::Signer::pubkey(&self.signer()?)),
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    let config_file = match cli.config {
+        Some(config_file) => config_file,
+        None => solana_cli_config::CONFIG_FILE
+            .as_ref()
+            .ok_or_else(|| anyhow!("unable to determine the Solana CLI config file"))?
+            .clone(),
+    };
+    let config = solana_cli_config::Config::load(&config_file).unwrap_or_default();
+    let commitment = CommitmentConfig::from_str(&config.commitment)
+        .with_context(|| format!("invalid commitment {}", config.commitment))?;
+
+    let context = CliContext {
+        rpc: RpcClient::new_with_commitment(
+            normalize_url(cli.url.unwrap_or(config.json_rpc_url)),
+            commitment,
+        ),
+        keypair_path: cli.keypair.unwrap_or(config.keypair_path),
+        output: cli.output,
+    };
+
+    match cli.command {
+        Command::InitVault { pool } => commands::init_vault(&context, &pool),
+        Command::Deposit {
+            pool,
+            amount,
+            unlock_timestamp,
+            duration,
+            beneficiary,
+        } => commands::deposit(
+            &context,
+            &pool,
+            amount,
+            unlock_timestamp,
+            duration,
+            beneficiary,
+        ),
+        Command::Withdraw { pool, amount, user } => {
+            commands::withdraw(&context, &pool, amount, user)
+        }
+        Command::Locks { user } => commands::locks(&context, user),
+        Command::PoolLocks { pool } => commands::pool_locks(&context, &pool),
+        Command::FeePreview { pool, user, amount } => {
+            commands::fee_preview(&context, &pool, user, amount)
+        }
+    }
+}
+
+/// Expands the Solana CLI's cluster monikers, e.g. `-u localhost` for a
+/// `solana-test-validator`.
+fn normalize_url(url: String) -> String {
+    match url.as_str() {
+        "l" | "localhost" => "http://localhost:8899".to_string(),
+        "d" | "devnet" => "https://api.devnet.solana.com".to_string(),
+        "t" | "testnet" => "https://api.testnet.solana.com".to_string(),
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com".to_string(),
+        _ => url,
+    }
+}
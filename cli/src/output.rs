@@ -0,0 +1,216 @@
+use std::fmt;
+
+use anyhow::Result;
+use serde::Serialize;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use tool_lp_client::{UserLock, WithdrawQuote};
+
+use crate::OutputFormat;
+
+pub fn print<T: Serialize + fmt::Display>(format: OutputFormat, value: &T) -> Result<()> {
+    match format {
+        OutputFormat::Display => println!("{value}"),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value)?),
+    }
+    Ok(())
+}
+
+#[derive(Serialize)]
+pub struct TransactionView {
+    #[serde(serialize_with = "display")]
+    pub signature: Signature,
+}
+
+impl fmt::Display for TransactionView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Signature: {}", self.signature)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LockView {
+    #[serde(serialize_with = "display")]
+    pub address: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub vault: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub pool_state: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub user: Pubkey,
+    pub amount: u64,
+    pub unlock_timestamp: i64,
+    /// Zero once unlocked.
+    pub seconds_remaining: i64,
+    pub is_permanent: bool,
+    pub rollover_duration: i64,
+    pub keeper_enabled: bool,
+    pub tokenized: bool,
+}
+
+impl LockView {
+    pub fn new(
+        address: Pubkey,
+        vault: Pubkey,
+        pool_state: Pubkey,
+        lock: &UserLock,
+        current_timestamp: i64,
+    ) -> Self {
+        Self {
+            address,
+            vault,
+            pool_state,
+            user: lock.user,
+            amount: lock.amount,
+            unlock_timestamp: lock.unlock_timestamp,
+            seconds_remaining: lock
+                .unlock_timestamp
+                .saturating_sub(current_timestamp)
+                .max(0),
+            is_permanent: lock.is_permanent,
+            rollover_duration: lock.rollover_duration,
+            keeper_enabled: lock.keeper_enabled,
+            tokenized: lock.is_tokenized(),
+        }
+    }
+}
+
+impl fmt::Display for LockView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Lock: {}", self.address)?;
+        writeln!(f, "  Pool: {}", self.pool_state)?;
+        writeln!(f, "  Vault: {}", self.vault)?;
+        writeln!(f, "  User: {}", self.user)?;
+        writeln!(f, "  Amount: {}", self.amount)?;
+        if self.is_permanent {
+            write!(f, "  Unlocks: never (permanent)")?;
+        } else if self.seconds_remaining == 0 {
+            write!(f, "  Unlocks: unlocked")?;
+        } else {
+            write!(
+                f,
+                "  Unlocks: {} (in {})",
+                self.unlock_timestamp,
+                format_duration(self.seconds_remaining)
+            )?;
+        }
+        if self.rollover_duration > 0 {
+            write!(
+                f,
+                "\n  Rollover: every {}",
+                format_duration(self.rollover_duration)
+            )?;
+        }
+        if self.keeper_enabled {
+            write!(f, "\n  Keeper: enabled")?;
+        }
+        if self.tokenized {
+            write!(f, "\n  Tokenized: yes")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct LocksView(pub Vec<LockView>);
+
+impl fmt::Display for LocksView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "No locks found");
+        }
+        for (index, lock) in self.0.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{lock}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplitView {
+    pub raw_amount: u64,
+    pub deposit_amount: u64,
+    pub growth_amount: u64,
+    pub fee_amount: u64,
+    pub payout_amount: u64,
+    pub keeper_tip_amount: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeePreviewView {
+    pub lp_amount: u64,
+    pub fee_bps: u16,
+    pub token_0: SplitView,
+    pub token_1: SplitView,
+}
+
+impl FeePreviewView {
+    pub fn new(quote: &WithdrawQuote, fee_bps: u16) -> Self {
+        Self {
+            lp_amount: quote.lp_token_amount,
+            fee_bps,
+            token_0: SplitView {
+                raw_amount: quote.raw_token_0_amount,
+                deposit_amount: quote.deposit_token_0_amount,
+                growth_amount: quote.growth_token_0_amount,
+                fee_amount: quote.fee_0_amount,
+                payout_amount: quote.net_token_0_amount,
+                keeper_tip_amount: quote.keeper_tip_0_amount,
+            },
+            token_1: SplitView {
+                raw_amount: quote.raw_token_1_amount,
+                deposit_amount: quote.deposit_token_1_amount,
+                growth_amount: quote.growth_token_1_amount,
+                fee_amount: quote.fee_1_amount,
+                payout_amount: quote.net_token_1_amount,
+                keeper_tip_amount: quote.keeper_tip_1_amount,
+            },
+        }
+    }
+}
+
+impl fmt::Display for FeePreviewView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "LP amount: {}", self.lp_amount)?;
+        writeln!(f, "Performance fee: {} bps of growth", self.fee_bps)?;
+        for (name, split) in [("Token 0", &self.token_0), ("Token 1", &self.token_1)] {
+            writeln!(f, "{name}:")?;
+            writeln!(f, "  Value:   {}", split.raw_amount)?;
+            writeln!(f, "  Deposit: {}", split.deposit_amount)?;
+            writeln!(f, "  Growth:  {}", split.growth_amount)?;
+            writeln!(f, "  Fee:     {}", split.fee_amount)?;
+            writeln!(f, "  Payout:  {}", split.payout_amount)?;
+            if split.keeper_tip_amount > 0 {
+                writeln!(
+                    f,
+                    "  Keeper tip, when a keeper withdraws: {}",
+                    split.keeper_tip_amount
+                )?;
+            }
+        }
+        write!(f, "Payouts are net of Token-2022 transfer fees")
+    }
+}
+
+fn format_duration(seconds: i64) -> String {
+    let days = seconds / 86_400;
+    let hours = seconds % 86_400 / 3_600;
+    let minutes = seconds % 3_600 / 60;
+    match (days, hours) {
+        (0, 0) => format!("{minutes}m {}s", seconds % 60),
+        (0, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h {minutes}m"),
+    }
+}
+
+fn display<T: fmt::Display, S: serde::Serializer>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
@@ -0,0 +1,220 @@
+//! Typed fetchers and decoders for tool-lp and Raydium accounts.
+
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
+use anchor_spl::{token_2022, token_interface::TokenAccount};
+use raydium_cp_swap::states::PoolState;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_filter::{Memcmp, RpcFilterType},
+    rpc_request::{TokenAccountsFilter, MAX_MULTIPLE_ACCOUNTS},
+};
+use tool_lp::{UserLock, Vault, MAX_REWARDS};
+
+use crate::{pda, ClientError, Result};
+
+/// Offset of `UserLock::user` in the account data.
+pub const USER_LOCK_USER_OFFSET: usize = 8;
+/// Offset of `UserLock::position_mint` in the account data.
+pub const USER_LOCK_POSITION_MINT_OFFSET: usize =
+    8 + 32 + 8 + 8 + 8 + 8 + 16 * MAX_REWARDS + 8 * MAX_REWARDS + 8 + 8;
+/// Offset of `UserLock::vault`, its last field, in the account data.
+pub const USER_LOCK_VAULT_OFFSET: usize = 8 + UserLock::LEN - 32;
+
+/// Token account holding a position NFT, and its owner.
+pub struct PositionHolder {
+    pub address: Pubkey,
+    pub owner: Pubkey,
+}
+
+pub fn decode_vault(data: &[u8]) -> Result<Vault> {
+    Ok(Vault::try_deserialize(&mut &data[..])?)
+}
+
+pub fn decode_user_lock(data: &[u8]) -> Result<UserLock> {
+    Ok(UserLock::try_deserialize(&mut &data[..])?)
+}
+
+/// `PoolState` is zero-copy, so it is read straight from the bytes after the
+/// discriminator rather than borsh-decoded.
+pub fn decode_pool_state(data: &[u8]) -> Result<PoolState> {
+    let discriminator_len = PoolState::DISCRIMINATOR.len();
+    let end = discriminator_len + std::mem::size_of::<PoolState>();
+    if data.len() < end || !data.starts_with(PoolState::DISCRIMINATOR) {
+        return Err(ClientError::InvalidAccountData);
+    }
+    bytemuck::try_pod_read_unaligned(&data[discriminator_len..end])
+        .map_err(|_| ClientError::InvalidAccountData)
+}
+
+pub fn fetch_vault(rpc: &RpcClient, address: &Pubkey) -> Result<Vault> {
+    decode_vault(&fetch_data(rpc, address, &tool_lp::ID)?)
+}
+
+/// Fetches the vault for a pool by its PDA.
+pub fn fetch_vault_for_pool(rpc: &RpcClient, pool_state: &Pubkey) -> Result<(Pubkey, Vault)> {
+    let (vault, _) = pda::vault(pool_state);
+    Ok((vault, fetch_vault(rpc, &vault)?))
+}
+
+pub fn fetch_user_lock(rpc: &RpcClient, address: &Pubkey) -> Result<UserLock> {
+    decode_user_lock(&fetch_data(rpc, address, &tool_lp::ID)?)
+}
+
+pub fn fetch_pool_state(rpc: &RpcClient, address: &Pubkey) -> Result<PoolState> {
+    decode_pool_state(&fetch_data(rpc, address, &raydium_cp_swap::ID)?)
+}
+
+/// Every vault the program has created.
+pub fn fetch_all_vaults(rpc: &RpcClient) -> Result<Vec<(Pubkey, Vault)>> {
+    let filters = vec![
+        RpcFilterType::DataSize((8 + Vault::LEN) as u64),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, Vault::DISCRIMINATOR)),
+    ];
+    fetch_program_accounts(rpc, filters, decode_vault)
+}
+
+/// Every lock, or only those keyed by `user` when given. A tokenized lock
+/// stays keyed by its depositor after its NFT changes hands; see
+/// `fetch_held_user_locks`.
+pub fn fetch_all_user_locks(
+    rpc: &RpcClient,
+    user: Option<&Pubkey>,
+) -> Result<Vec<(Pubkey, UserLock)>> {
+    let filters = user
+        .map(|user| memcmp(USER_LOCK_USER_OFFSET, user))
+        .into_iter()
+        .collect();
+    fetch_user_locks(rpc, filters)
+}
+
+/// Locks held in the vault for `pool_state`.
+pub fn fetch_pool_user_locks(
+    rpc: &RpcClient,
+    pool_state: &Pubkey,
+) -> Result<Vec<(Pubkey, UserLock)>> {
+    let (vault, _) = pda::vault(pool_state);
+    fetch_user_locks(rpc, vec![memcmp(USER_LOCK_VAULT_OFFSET, &vault)])
+}
+
+/// Locks `holder` controls: its untokenized locks, and the tokenized ones
+/// whose position NFT it holds, whoever deposited them. Every Token-2022
+/// account of `holder` with a balance of one is looked up as a position.
+pub fn fetch_held_user_locks(rpc: &RpcClient, holder: &Pubkey) -> Result<Vec<(Pubkey, UserLock)>> {
+    let mut locks = fetch_all_user_locks(rpc, Some(holder))?;
+    locks.retain(|(_, lock)| !lock.is_tokenized());
+    for position_mint in fetch_held_nft_mints(rpc, holder)? {
+        let filters = vec![memcmp(USER_LOCK_POSITION_MINT_OFFSET, &position_mint)];
+        locks.extend(fetch_user_locks(rpc, filters)?);
+    }
+    Ok(locks)
+}
+
+/// Finds the token account holding a position NFT.
+pub fn fetch_position_holder(rpc: &RpcClient, position_mint: &Pubkey) -> Result<PositionHolder> {
+    for balance in rpc.get_token_largest_accounts(position_mint)? {
+        if balance.amount.amount != "1" {
+            continue;
+        }
+        let address: Pubkey = balance
+            .address
+            .parse()
+            .map_err(|_| ClientError::InvalidAccountData)?;
+        let account = rpc.get_account(&address)?;
+        let token_account = TokenAccount::try_deserialize(&mut &account.data[..])?;
+        return Ok(PositionHolder {
+            address,
+            owner: token_account.owner,
+        });
+    }
+    Err(ClientError::AccountNotFound(*position_mint))
+}
+
+/// Who withdrawals from `lock` pay: the holder of its position NFT once it is
+/// tokenized, otherwise its `user`.
+pub fn fetch_lock_owner(rpc: &RpcClient, lock: &UserLock) -> Result<Pubkey> {
+    if lock.is_tokenized() {
+        Ok(fetch_position_holder(rpc, &lock.position_mint)?.owner)
+    } else {
+        Ok(lock.user)
+    }
+}
+
+/// Balance of an SPL Token or Token-2022 account.
+pub fn fetch_token_amount(rpc: &RpcClient, address: &Pubkey) -> Result<u64> {
+    let account = rpc.get_account(address)?;
+    let token_account = TokenAccount::try_deserialize(&mut &account.data[..])?;
+    Ok(token_account.amount)
+}
+
+/// Returns whether an account exists, without failing on a missing one.
+pub fn account_exists(rpc: &RpcClient, address: &Pubkey) -> Result<bool> {
+    let response = rpc.get_account_with_commitment(address, rpc.commitment())?;
+    Ok(response.value.is_some())
+}
+
+fn fetch_user_locks(
+    rpc: &RpcClient,
+    extra_filters: Vec<RpcFilterType>,
+) -> Result<Vec<(Pubkey, UserLock)>> {
+    let mut filters = vec![
+        RpcFilterType::DataSize((8 + UserLock::LEN) as u64),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(0, UserLock::DISCRIMINATOR)),
+    ];
+    filters.extend(extra_filters);
+    fetch_program_accounts(rpc, filters, decode_user_lock)
+}
+
+/// Mints of the Token-2022 accounts `owner` holds exactly one token of.
+fn fetch_held_nft_mints(rpc: &RpcClient, owner: &Pubkey) -> Result<Vec<Pubkey>> {
+    let addresses = rpc
+        .get_token_accounts_by_owner(owner, TokenAccountsFilter::ProgramId(token_2022::ID))?
+        .into_iter()
+        .map(|keyed| {
+            keyed
+                .pubkey
+                .parse()
+                .map_err(|_| ClientError::InvalidAccountData)
+        })
+        .collect::<Result<Vec<Pubkey>>>()?;
+    let mut mints = Vec::new();
+    for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        for account in rpc.get_multiple_accounts(chunk)?.into_iter().flatten() {
+            let token_account = TokenAccount::try_deserialize(&mut &account.data[..])?;
+            if token_account.amount == 1 {
+                mints.push(token_account.mint);
+            }
+        }
+    }
+    Ok(mints)
+}
+
+fn memcmp(offset: usize, key: &Pubkey) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp::new_base58_encoded(offset, key.as_ref()))
+}
+
+fn fetch_program_accounts<T>(
+    rpc: &RpcClient,
+    filters: Vec<RpcFilterType>,
+    decode: fn(&[u8]) -> Result<T>,
+) -> Result<Vec<(Pubkey, T)>> {
+    let config = RpcProgramAccountsConfig {
+        filters: Some(filters),
+        ..Default::default()
+    };
+    rpc.get_program_accounts_with_config(&tool_lp::ID, config)?
+        .into_iter()
+        .map(|(address, account)| Ok((address, decode(&account.data)?)))
+        .collect()
+}
+
+fn fetch_data(rpc: &RpcClient, address: &Pubkey, owner: &Pubkey) -> Result<Vec<u8>> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())?
+        .value
+        .ok_or(ClientError::AccountNotFound(*address))?;
+    if account.owner != *owner {
+        return Err(ClientError::InvalidOwner(*address, *owner));
+    }
+    Ok(account.data)
+}
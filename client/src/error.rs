@@ -0,0 +1,29 @@
+use anchor_lang::prelude::Pubkey;
+
+pub type Result<T> = std::result::Result<T, ClientError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error("rpc error: {0}")]
+    Rpc(#[from] solana_client::client_error::ClientError),
+    #[error("account {0} not found")]
+    AccountNotFound(Pubkey),
+    #[error("account {0} is not owned by {1}")]
+    InvalidOwner(Pubkey, Pubkey),
+    #[error("account data does not match the expected type")]
+    InvalidAccountData,
+    #[error("arithmetic error")]
+    Math(tool_lp_math::MathError),
+    #[error("simulation failed: {0}")]
+    Simulation(String),
+    #[error("return data does not match the expected type")]
+    InvalidReturnData,
+    #[error(transparent)]
+    Anchor(#[from] anchor_lang::error::Error),
+}
+
+impl From<tool_lp_math::MathError> for ClientError {
+    fn from(err: tool_lp_math::MathError) -> Self {
+        ClientError::Math(err)
+    }
+}
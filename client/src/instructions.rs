@@ -0,0 +1,317 @@
+//! Instruction builders. Each one fetches the vault and Raydium pool so callers
+//! only pass the pool, the signers and the instruction arguments.
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{instruction::Instruction, system_program, sysvar},
+    AccountDeserialize, AnchorDeserialize, AnchorSerialize, InstructionData, ToAccountMetas,
+};
+use anchor_spl::{associated_token, token, token_2022};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use raydium_cp_swap::states::PoolState;
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcSimulateTransactionConfig};
+use solana_sdk::transaction::Transaction;
+use tool_lp::WithdrawQuote;
+
+use crate::{
+    accounts::{
+        account_exists, fetch_pool_state, fetch_position_holder, fetch_user_lock,
+        fetch_vault_for_pool,
+    },
+    pda, ClientError, Result,
+};
+
+/// Pool accounts every builder needs, copied out of the packed `PoolState`.
+struct Pool {
+    token_0_vault: Pubkey,
+    token_1_vault: Pubkey,
+    token_0_mint: Pubkey,
+    token_1_mint: Pubkey,
+    token_0_program: Pubkey,
+    token_1_program: Pubkey,
+    lp_mint: Pubkey,
+}
+
+impl From<&PoolState> for Pool {
+    fn from(pool_state: &PoolState) -> Self {
+        Self {
+            token_0_vault: pool_state.token_0_vault,
+            token_1_vault: pool_state.token_1_vault,
+            token_0_mint: pool_state.token_0_mint,
+            token_1_mint: pool_state.token_1_mint,
+            token_0_program: pool_state.token_0_program,
+            token_1_program: pool_state.token_1_program,
+            lp_mint: pool_state.lp_mint,
+        }
+    }
+}
+
+pub fn initialize_vault(
+    rpc: &RpcClient,
+    initializer: &Pubkey,
+    pool_state: &Pubkey,
+) -> Result<Instruction> {
+    let pool = Pool::from(&fetch_pool_state(rpc, pool_state)?);
+    let (vault, _) = pda::vault(pool_state);
+    let (vault_authority, _) = pda::vault_authority(pool_state, &vault);
+    let (pool_allowlist, _) = pda::pool_allowlist(pool_state);
+
+    let accounts = tool_lp::accounts::InitializeVault {
+        vault,
+        initializer: *initializer,
+        token_0_vault: pool.token_0_vault,
+        token_1_vault: pool.token_1_vault,
+        vault_0_mint: pool.token_0_mint,
+        vault_1_mint: pool.token_1_mint,
+        pool_state: *pool_state,
+        token_mint: pool.lp_mint,
+        vault_token_account: pda::vault_token(pool_state, &vault).0,
+        vault_token_0_account: pda::associated_token(
+            &vault_authority,
+            &pool.token_0_mint,
+            &pool.token_0_program,
+        ),
+        vault_token_1_account: pda::associated_token(
+            &vault_authority,
+            &pool.token_1_mint,
+            &pool.token_1_program,
+        ),
+        vault_authority,
+        system_program: system_program::ID,
+        token_program: token::ID,
+        token_0_program: pool.token_0_program,
+        token_1_program: pool.token_1_program,
+        associated_token_program: associated_token::ID,
+        config: pda::config().0,
+        pool_allowlist: account_exists(rpc, &pool_allowlist)?.then_some(pool_allowlist),
+    };
+
+    Ok(Instruction {
+        program_id: tool_lp::ID,
+        accounts: accounts.to_account_metas(None),
+        data: tool_lp::instruction::InitializeVault.data(),
+    })
+}
+
+/// Locks LP from `user`'s associated LP account into `beneficiary`'s lock. A
+/// tokenized lock only takes deposits from the holder of its position NFT.
+pub fn deposit(
+    rpc: &RpcClient,
+    user: &Pubkey,
+    payer: &Pubkey,
+    beneficiary: &Pubkey,
+    pool_state: &Pubkey,
+    amount: u64,
+    unlock_timestamp: i64,
+) -> Result<Instruction> {
+    let pool = Pool::from(&fetch_pool_state(rpc, pool_state)?);
+    let (vault_key, vault) = fetch_vault_for_pool(rpc, pool_state)?;
+    let (user_lock, _) = pda::user_lock(&vault_key, beneficiary);
+    let position_mint = if account_exists(rpc, &user_lock)? {
+        Some(fetch_user_lock(rpc, &user_lock)?)
+            .filter(|lock| lock.is_tokenized())
+            .map(|lock| lock.position_mint)
+    } else {
+        None
+    };
+
+    let accounts = tool_lp::accounts::Deposit {
+        vault: vault_key,
+        user: *user,
+        beneficiary: *beneficiary,
+        user_lock,
+        user_token_account: pda::associated_token(user, &pool.lp_mint, &token::ID),
+        vault_token_account: vault.vault_token_account,
+        token_mint: pool.lp_mint,
+        pool_state: *pool_state,
+        token_0_vault: pool.token_0_vault,
+        token_1_vault: pool.token_1_vault,
+        token_program: token::ID,
+        clock: sysvar::clock::ID,
+        system_program: system_program::ID,
+        position_token_account: position_mint
+            .map(|position_mint| pda::associated_token(user, &position_mint, &token_2022::ID)),
+        payer: Some(*payer),
+    };
+
+    Ok(Instruction {
+        program_id: tool_lp::ID,
+        accounts: accounts.to_account_metas(None),
+        data: tool_lp::instruction::Deposit {
+            amount,
+            unlock_timestamp,
+        }
+        .data(),
+    })
+}
+
+/// Withdraws from the lock at `user_lock` to its owner's associated token
+/// accounts, which must already exist. When `signer` is not the owner it
+/// withdraws as a keeper and collects the tip if it holds both pool tokens'
+/// associated accounts.
+pub fn withdraw(
+    rpc: &RpcClient,
+    signer: &Pubkey,
+    payer: &Pubkey,
+    pool_state: &Pubkey,
+    user_lock: &Pubkey,
+    lp_token_amount: u64,
+) -> Result<Instruction> {
+    let pool = Pool::from(&fetch_pool_state(rpc, pool_state)?);
+    let (vault_key, vault) = fetch_vault_for_pool(rpc, pool_state)?;
+    let lock = fetch_user_lock(rpc, user_lock)?;
+    let (vault_authority, _) = pda::vault_authority(pool_state, &vault_key);
+
+    let (owner, position_token_account) = if lock.is_tokenized() {
+        let holder = fetch_position_holder(rpc, &lock.position_mint)?;
+        (holder.owner, Some(holder.address))
+    } else {
+        (lock.user, None)
+    };
+    // Emptying a tokenized lock burns its position NFT.
+    let position_mint =
+        (lock.is_tokenized() && lp_token_amount == lock.amount).then_some(lock.position_mint);
+
+    let keeper_token_0_account =
+        pda::associated_token(signer, &pool.token_0_mint, &pool.token_0_program);
+    let keeper_token_1_account =
+        pda::associated_token(signer, &pool.token_1_mint, &pool.token_1_program);
+    let keeper_accounts = *signer != owner
+        && account_exists(rpc, &keeper_token_0_account)?
+        && account_exists(rpc, &keeper_token_1_account)?;
+
+    let accounts = tool_lp::accounts::Withdraw {
+        vault: vault_key,
+        user: *signer,
+        user_lock: *user_lock,
+        position_token_account,
+        position_mint,
+        vault_token_account: vault.vault_token_account,
+        vault_token_0_account: pda::associated_token(
+            &vault_authority,
+            &pool.token_0_mint,
+            &pool.token_0_program,
+        ),
+        vault_token_1_account: pda::associated_token(
+            &vault_authority,
+            &pool.token_1_mint,
+            &pool.token_1_program,
+        ),
+        user_token_0_account: pda::associated_token(
+            &owner,
+            &pool.token_0_mint,
+            &pool.token_0_program,
+        ),
+        user_token_1_account: pda::associated_token(
+            &owner,
+            &pool.token_1_mint,
+            &pool.token_1_program,
+        ),
+        keeper_token_0_account: keeper_accounts.then_some(keeper_token_0_account),
+        keeper_token_1_account: keeper_accounts.then_some(keeper_token_1_account),
+        fee_recipient: vault.fee_recipient,
+        fee_recipient_token_0_account: pda::associated_token(
+            &vault.fee_recipient,
+            &pool.token_0_mint,
+            &pool.token_0_program,
+        ),
+        fee_recipient_token_1_account: pda::associated_token(
+            &vault.fee_recipient,
+            &pool.token_1_mint,
+            &pool.token_1_program,
+        ),
+        token_0_vault: pool.token_0_vault,
+        token_1_vault: pool.token_1_vault,
+        vault_authority,
+        pool_state: *pool_state,
+        cp_swap_program: raydium_cp_swap::ID,
+        authority: pda::raydium_authority().0,
+        token_mint: vault.token_mint,
+        vault_0_mint: pool.token_0_mint,
+        vault_1_mint: pool.token_1_mint,
+        lp_mint: pool.lp_mint,
+        token_0_program: pool.token_0_program,
+        token_1_program: pool.token_1_program,
+        token_program: token::ID,
+        token_program_2022: token_2022::ID,
+        memo_program: spl_memo::id(),
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+        clock: sysvar::clock::ID,
+        payer: Some(*payer),
+    };
+
+    Ok(Instruction {
+        program_id: tool_lp::ID,
+        accounts: accounts.to_account_metas(None),
+        data: tool_lp::instruction::Withdraw { lp_token_amount }.data(),
+    })
+}
+
+/// Quotes a withdrawal by simulating the program's `quote_withdraw`, so the
+/// Token-2022 transfer fees and keeper tips are the ones `withdraw` would apply.
+/// `payer` only has to exist to pay for the simulation; nothing is signed or sent.
+pub fn quote_withdraw(
+    rpc: &RpcClient,
+    payer: &Pubkey,
+    pool_state: &Pubkey,
+    user_lock: &Pubkey,
+    lp_token_amount: u64,
+) -> Result<WithdrawQuote> {
+    let pool = Pool::from(&fetch_pool_state(rpc, pool_state)?);
+    let (vault, _) = pda::vault(pool_state);
+
+    let accounts = tool_lp::accounts::QuoteWithdraw {
+        vault,
+        user_lock: *user_lock,
+        pool_state: *pool_state,
+        token_0_vault: pool.token_0_vault,
+        token_1_vault: pool.token_1_vault,
+        vault_0_mint: pool.token_0_mint,
+        vault_1_mint: pool.token_1_mint,
+    };
+    let instruction = Instruction {
+        program_id: tool_lp::ID,
+        accounts: accounts.to_account_metas(None),
+        data: tool_lp::instruction::QuoteWithdraw { lp_token_amount }.data(),
+    };
+    let transaction = Transaction::new_with_payer(&[instruction], Some(payer));
+    let simulation = rpc
+        .simulate_transaction_with_config(
+            &transaction,
+            RpcSimulateTransactionConfig {
+                sig_verify: false,
+                replace_recent_blockhash: true,
+                ..Default::default()
+            },
+        )?
+        .value;
+    if let Some(err) = simulation.err {
+        let logs = simulation.logs.unwrap_or_default().join("\n");
+        return Err(ClientError::Simulation(format!("{err}\n{logs}")));
+    }
+
+    let (data, _) = simulation
+        .return_data
+        .ok_or(ClientError::InvalidReturnData)?
+        .data;
+    let data = STANDARD
+        .decode(data)
+        .map_err(|_| ClientError::InvalidReturnData)?;
+    decode_withdraw_quote(&data)
+}
+
+/// Decodes the `WithdrawQuote` returned by `quote_withdraw`. Return data may
+/// arrive with its trailing zero bytes trimmed, so they are restored first.
+pub fn decode_withdraw_quote(data: &[u8]) -> Result<WithdrawQuote> {
+    let mut full = Vec::new();
+    WithdrawQuote::default()
+        .serialize(&mut full)
+        .map_err(|_| ClientError::InvalidReturnData)?;
+    if data.len() > full.len() {
+        return Err(ClientError::InvalidReturnData);
+    }
+    full[..data.len()].copy_from_slice(data);
+    full[data.len()..].fill(0);
+    WithdrawQuote::deserialize(&mut &full[..]).map_err(|_| ClientError::InvalidReturnData)
+}
@@ -0,0 +1,19 @@
+//! Client for the tool-lp program: PDA derivation, account decoders and
+//! instruction builders that resolve the Raydium pool accounts over RPC.
+
+pub mod accounts;
+pub mod error;
+pub mod instructions;
+pub mod pda;
+
+pub use accounts::{
+    decode_pool_state, decode_user_lock, decode_vault, fetch_all_user_locks, fetch_all_vaults,
+    fetch_held_user_locks, fetch_lock_owner, fetch_pool_state, fetch_pool_user_locks,
+    fetch_position_holder, fetch_user_lock, fetch_vault, fetch_vault_for_pool, PositionHolder,
+};
+pub use error::{ClientError, Result};
+pub use instructions::{
+    decode_withdraw_quote, deposit, initialize_vault, quote_withdraw, withdraw,
+};
+pub use tool_lp::{UserLock, Vault, WithdrawQuote, ID};
+pub use tool_lp_math as math;
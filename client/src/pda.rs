@@ -0,0 +1,57 @@
+//! Program-derived addresses used by tool-lp, mirroring the seeds in the program.
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use tool_lp::{
+    CONFIG_SEED, ID, POOL_ALLOWLIST_SEED, POSITION_MINT_SEED, USER_LOCK_SEED, VAULT_AUTHORITY_SEED,
+    VAULT_SEED, VAULT_TOKEN_SEED,
+};
+
+pub fn config() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG_SEED], &ID)
+}
+
+pub fn pool_allowlist(pool_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POOL_ALLOWLIST_SEED, pool_state.as_ref()], &ID)
+}
+
+pub fn vault(pool_state: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED, pool_state.as_ref()], &ID)
+}
+
+/// The vault's LP token account.
+pub fn vault_token(pool_state: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_TOKEN_SEED, pool_state.as_ref(), vault.as_ref()],
+        &ID,
+    )
+}
+
+/// Owner of the vault's LP and pool token accounts.
+pub fn vault_authority(pool_state: &Pubkey, vault: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_AUTHORITY_SEED, pool_state.as_ref(), vault.as_ref()],
+        &ID,
+    )
+}
+
+pub fn user_lock(vault: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[USER_LOCK_SEED, vault.as_ref(), user.as_ref()], &ID)
+}
+
+/// Mint of the lock's position NFT.
+pub fn position_mint(user_lock: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[POSITION_MINT_SEED, user_lock.as_ref()], &ID)
+}
+
+/// Raydium's pool vault and LP mint authority.
+pub fn raydium_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[raydium_cp_swap::AUTH_SEED.as_bytes()],
+        &raydium_cp_swap::ID,
+    )
+}
+
+pub fn associated_token(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
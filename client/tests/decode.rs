@@ -0,0 +1,112 @@
+//! Decoders for account and return data, without a cluster.
+
+use anchor_lang::{prelude::Pubkey, AnchorSerialize, Discriminator};
+use raydium_cp_swap::states::PoolState;
+use tool_lp_client::{
+    accounts::{USER_LOCK_POSITION_MINT_OFFSET, USER_LOCK_USER_OFFSET, USER_LOCK_VAULT_OFFSET},
+    decode_pool_state, decode_user_lock, decode_withdraw_quote, ClientError, UserLock,
+    WithdrawQuote,
+};
+
+fn pool_state_data(pool_state: &PoolState) -> Vec<u8> {
+    let mut data = PoolState::DISCRIMINATOR.to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(pool_state));
+    data
+}
+
+#[test]
+fn pool_state_is_read_after_the_discriminator() {
+    let mut pool_state: PoolState = bytemuck::Zeroable::zeroed();
+    pool_state.lp_supply = 1_000_000;
+    pool_state.token_0_vault = Pubkey::new_unique();
+    // Accounts may be larger than the struct.
+    let mut data = pool_state_data(&pool_state);
+    data.extend_from_slice(&[0; 16]);
+
+    let decoded = decode_pool_state(&data).unwrap();
+
+    assert_eq!({ decoded.lp_supply }, 1_000_000);
+    assert_eq!({ decoded.token_0_vault }, { pool_state.token_0_vault });
+}
+
+#[test]
+fn pool_state_with_another_discriminator_is_rejected() {
+    let mut data = pool_state_data(&bytemuck::Zeroable::zeroed());
+    data[0] ^= 1;
+
+    assert!(matches!(
+        decode_pool_state(&data),
+        Err(ClientError::InvalidAccountData)
+    ));
+}
+
+#[test]
+fn truncated_pool_state_is_rejected() {
+    let data = pool_state_data(&bytemuck::Zeroable::zeroed());
+
+    assert!(matches!(
+        decode_pool_state(&data[..data.len() - 1]),
+        Err(ClientError::InvalidAccountData)
+    ));
+}
+
+#[test]
+fn withdraw_quote_survives_trimmed_trailing_zeros() {
+    let quote = WithdrawQuote {
+        lp_token_amount: 100,
+        raw_token_0_amount: 1_500,
+        net_token_0_amount: 1_400,
+        net_token_1_amount: 3_000,
+        ..Default::default()
+    };
+    let mut data = Vec::new();
+    quote.serialize(&mut data).unwrap();
+    // The keeper tips are zero, so the runtime may drop them from the end.
+    while data.last() == Some(&0) {
+        data.pop();
+    }
+
+    let decoded = decode_withdraw_quote(&data).unwrap();
+
+    assert_eq!(decoded.lp_token_amount, 100);
+    assert_eq!(decoded.raw_token_0_amount, 1_500);
+    assert_eq!(decoded.net_token_0_amount, 1_400);
+    assert_eq!(decoded.net_token_1_amount, 3_000);
+    assert_eq!(decoded.keeper_tip_1_amount, 0);
+}
+
+#[test]
+fn oversized_withdraw_quote_is_rejected() {
+    let mut data = Vec::new();
+    WithdrawQuote::default().serialize(&mut data).unwrap();
+    data.push(1);
+
+    assert!(matches!(
+        decode_withdraw_quote(&data),
+        Err(ClientError::InvalidReturnData)
+    ));
+}
+
+#[test]
+fn user_lock_filter_offsets_match_its_layout() {
+    let (user, position_mint, vault) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let mut data = UserLock::DISCRIMINATOR.to_vec();
+    data.resize(8 + UserLock::LEN, 0);
+    for (offset, key) in [
+        (USER_LOCK_USER_OFFSET, user),
+        (USER_LOCK_POSITION_MINT_OFFSET, position_mint),
+        (USER_LOCK_VAULT_OFFSET, vault),
+    ] {
+        data[offset..offset + 32].copy_from_slice(key.as_ref());
+    }
+
+    let decoded = decode_user_lock(&data).unwrap();
+
+    assert_eq!(decoded.user, user);
+    assert_eq!(decoded.position_mint, position_mint);
+    assert_eq!(decoded.vault, vault);
+}
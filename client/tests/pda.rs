@@ -0,0 +1,72 @@
+//! PDA helpers against the seeds spelled out, so a seed drifting between the
+//! program and the client shows up here.
+
+use anchor_lang::prelude::Pubkey;
+use tool_lp_client::{pda, ID};
+
+fn assert_derived(derived: (Pubkey, u8), seeds: &[&[u8]], program_id: &Pubkey) {
+    let (address, bump) = derived;
+    let mut seeds = seeds.to_vec();
+    let bump = [bump];
+    seeds.push(&bump);
+    assert_eq!(
+        Pubkey::create_program_address(&seeds, program_id).unwrap(),
+        address
+    );
+}
+
+#[test]
+fn vault_accounts_are_derived_from_the_pool() {
+    let pool_state = Pubkey::new_unique();
+    let (vault, _) = pda::vault(&pool_state);
+
+    assert_derived(
+        pda::vault(&pool_state),
+        &[b"vault", pool_state.as_ref()],
+        &ID,
+    );
+    assert_derived(
+        pda::vault_token(&pool_state, &vault),
+        &[b"vault-token", pool_state.as_ref(), vault.as_ref()],
+        &ID,
+    );
+    assert_derived(
+        pda::vault_authority(&pool_state, &vault),
+        &[b"vault-authority", pool_state.as_ref(), vault.as_ref()],
+        &ID,
+    );
+    assert_derived(
+        pda::pool_allowlist(&pool_state),
+        &[b"pool-allowlist", pool_state.as_ref()],
+        &ID,
+    );
+    assert_ne!(vault, pda::vault(&Pubkey::new_unique()).0);
+}
+
+#[test]
+fn lock_accounts_are_derived_from_the_vault_and_user() {
+    let vault = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let (user_lock, _) = pda::user_lock(&vault, &user);
+
+    assert_derived(
+        pda::user_lock(&vault, &user),
+        &[b"user-lock", vault.as_ref(), user.as_ref()],
+        &ID,
+    );
+    assert_derived(
+        pda::position_mint(&user_lock),
+        &[b"position-mint", user_lock.as_ref()],
+        &ID,
+    );
+    assert_derived(pda::config(), &[b"config"], &ID);
+}
+
+#[test]
+fn raydium_authority_belongs_to_raydium() {
+    assert_derived(
+        pda::raydium_authority(),
+        &[raydium_cp_swap::AUTH_SEED.as_bytes()],
+        &raydium_cp_swap::ID,
+    );
+}
@@ -0,0 +1,104 @@
+//! Decoding of tool-lp events from the `emit!` lines in transaction logs.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tool_lp::{
+    BatchWithdrawResultEvent, DepositEvent, KeeperTipPaidEvent, KeeperUpdatedEvent,
+    LockExtendedEvent, LockMadePermanentEvent, LockRolledOverEvent, LockSplitEvent,
+    LockTransferredEvent, LockWeightUpdatedEvent, LocksMergedEvent, LpFeesClaimedEvent,
+    PositionMintedEvent, ProposalCreatedEvent, ProposalExecutedEvent, RewardAddedEvent,
+    RewardsClaimedEvent, RolloverUpdatedEvent, VoteCastEvent, WithdrawEvent,
+};
+
+use crate::{IndexerError, Result};
+
+macro_rules! tool_lp_events {
+    ($($variant:ident($event:ident)),* $(,)?) => {
+        /// Any event the program emits.
+        pub enum ToolLpEvent {
+            $($variant($event),)*
+        }
+
+        impl ToolLpEvent {
+            /// Decodes discriminator-prefixed event data. Returns `None` for
+            /// data that is not a known tool-lp event.
+            pub fn decode(data: &[u8]) -> Result<Option<Self>> {
+                $(
+                    if let Some(mut body) = data.strip_prefix($event::DISCRIMINATOR) {
+                        let event = $event::deserialize(&mut body)
+                            .map_err(|_| IndexerError::InvalidEvent(stringify!($event)))?;
+                        return Ok(Some(Self::$variant(event)));
+                    }
+                )*
+                Ok(None)
+            }
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$variant(_) => stringify!($event),)*
+                }
+            }
+        }
+    };
+}
+
+tool_lp_events! {
+    Deposit(DepositEvent),
+    Withdraw(WithdrawEvent),
+    LockExtended(LockExtendedEvent),
+    LockWeightUpdated(LockWeightUpdatedEvent),
+    RewardAdded(RewardAddedEvent),
+    RewardsClaimed(RewardsClaimedEvent),
+    PositionMinted(PositionMintedEvent),
+    LockTransferred(LockTransferredEvent),
+    LockSplit(LockSplitEvent),
+    LocksMerged(LocksMergedEvent),
+    RolloverUpdated(RolloverUpdatedEvent),
+    LockRolledOver(LockRolledOverEvent),
+    KeeperUpdated(KeeperUpdatedEvent),
+    KeeperTipPaid(KeeperTipPaidEvent),
+    LockMadePermanent(LockMadePermanentEvent),
+    LpFeesClaimed(LpFeesClaimedEvent),
+    ProposalCreated(ProposalCreatedEvent),
+    VoteCast(VoteCastEvent),
+    ProposalExecuted(ProposalExecutedEvent),
+    BatchWithdrawResult(BatchWithdrawResultEvent),
+}
+
+/// Extracts tool-lp events from a transaction's log messages, in emission order.
+/// `Program data:` lines are attributed to the program on top of the invocation
+/// stack, so events logged by other programs are skipped.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Result<Vec<ToolLpEvent>> {
+    let program_id = tool_lp::ID.to_string();
+    let mut stack: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let log = log.as_ref();
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if stack.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+            let data = STANDARD
+                .decode(data)
+                .map_err(|_| IndexerError::InvalidLog(log.to_string()))?;
+            events.extend(ToolLpEvent::decode(&data)?);
+        } else if let Some(rest) = log.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some(program), Some("invoke")) => stack.push(program),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+/// Address of the lock `user` holds in `vault`.
+pub fn user_lock_address(vault: &Pubkey, user: &Pubkey) -> Pubkey {
+    tool_lp_client::pda::user_lock(vault, user).0
+}
@@ -0,0 +1,355 @@
+//! Replays events into the store and keeps it in sync with the cluster.
+
+use std::str::FromStr;
+
+use anchor_lang::prelude::Pubkey;
+use rusqlite::Connection;
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_sdk::signature::Signature;
+use solana_transaction_status_client_types::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+    UiTransactionEncoding,
+};
+
+use crate::{
+    events::{parse_logs, user_lock_address, ToolLpEvent},
+    store::{self, LockRow, VaultRow},
+    IndexerError, Result, Store,
+};
+
+/// A vault whose indexed totals disagree with its on-chain account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Discrepancy {
+    pub vault: Pubkey,
+    pub indexed_total_locked: u64,
+    pub onchain_total_locked: u64,
+    pub indexed_total_permanent_locked: u64,
+    pub onchain_total_permanent_locked: u64,
+}
+
+pub struct Indexer {
+    store: Store,
+}
+
+impl Indexer {
+    pub fn new(store: Store) -> Self {
+        Self { store }
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Applies one transaction's events atomically. Events already indexed are
+    /// skipped, so a transaction can be replayed safely. Returns how many were new.
+    pub fn index_events(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        events: &[ToolLpEvent],
+    ) -> Result<usize> {
+        let tx = self.store.transaction()?;
+        let mut applied = 0;
+        for (index, event) in events.iter().enumerate() {
+            if store::insert_event(&tx, signature, index, slot, event.name())? {
+                apply(&tx, event)?;
+                applied += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(applied)
+    }
+
+    /// Indexes a fetched transaction from its log messages. Failed
+    /// transactions emit nothing.
+    pub fn index_transaction(
+        &mut self,
+        signature: &str,
+        transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    ) -> Result<usize> {
+        let meta = transaction
+            .transaction
+            .meta
+            .as_ref()
+            .ok_or_else(|| IndexerError::InvalidTransaction(signature.to_string()))?;
+        if meta.err.is_some() {
+            return Ok(0);
+        }
+
+        let events = match &meta.log_messages {
+            OptionSerializer::Some(logs) => parse_logs(logs)?,
+            _ => Vec::new(),
+        };
+        self.index_events(signature, transaction.slot, &events)
+    }
+
+    /// Indexes every program transaction newer than the last one indexed,
+    /// oldest first. Returns how many events were applied.
+    pub fn sync(&mut self, rpc: &RpcClient) -> Result<usize> {
+        let until = self
+            .store
+            .last_signature()?
+            .map(|signature| Signature::from_str(&signature))
+            .transpose()
+            .map_err(|_| IndexerError::InvalidTransaction("sync cursor".to_string()))?;
+
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = rpc.get_signatures_for_address_with_config(
+                &tool_lp::ID,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: None,
+                    commitment: Some(rpc.commitment()),
+                },
+            )?;
+            let Some(oldest) = page.last() else {
+                break;
+            };
+            before = Some(
+                Signature::from_str(&oldest.signature)
+                    .map_err(|_| IndexerError::InvalidTransaction(oldest.signature.clone()))?,
+            );
+            signatures.extend(page);
+        }
+
+        let mut applied = 0;
+        for status in signatures.into_iter().rev() {
+            if status.err.is_none() {
+                let signature = Signature::from_str(&status.signature)
+                    .map_err(|_| IndexerError::InvalidTransaction(status.signature.clone()))?;
+                let transaction = rpc.get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(rpc.commitment()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )?;
+                applied += self.index_transaction(&status.signature, &transaction)?;
+            }
+            let tx = self.store.transaction()?;
+            store::set_last_signature(&tx, &status.signature)?;
+            tx.commit()?;
+        }
+        Ok(applied)
+    }
+
+    /// Compares each indexed vault with its on-chain `Vault` account.
+    pub fn reconcile(&self, rpc: &RpcClient) -> Result<Vec<Discrepancy>> {
+        let mut discrepancies = Vec::new();
+        for row in self.store.vaults()? {
+            let vault = tool_lp_client::fetch_vault(rpc, &row.address)?;
+            if vault.total_locked != row.total_locked
+                || vault.total_permanent_locked != row.total_permanent_locked
+            {
+                discrepancies.push(Discrepancy {
+                    vault: row.address,
+                    indexed_total_locked: row.total_locked,
+                    onchain_total_locked: vault.total_locked,
+                    indexed_total_permanent_locked: row.total_permanent_locked,
+                    onchain_total_permanent_locked: vault.total_permanent_locked,
+                });
+            }
+        }
+        Ok(discrepancies)
+    }
+}
+
+fn apply(conn: &Connection, event: &ToolLpEvent) -> Result<()> {
+    match event {
+        ToolLpEvent::Deposit(event) => {
+            let mut vault = load_vault(conn, &event.vault)?;
+            vault.pool_state = Some(event.pool_state);
+            let mut lock = load_lock(conn, &event.vault, &event.user)?;
+            lock.amount = lock.amount.saturating_add(event.amount);
+            lock.unlock_timestamp = event.unlock_timestamp;
+            lock.updated_at = event.timestamp;
+            vault.total_locked = vault.total_locked.saturating_add(event.amount);
+            if lock.is_permanent {
+                vault.total_permanent_locked =
+                    vault.total_permanent_locked.saturating_add(event.amount);
+            }
+            store::put_lock(conn, &lock)?;
+            store::put_vault(conn, &vault)?;
+        }
+        ToolLpEvent::Withdraw(event) => {
+            let mut vault = load_vault(conn, &event.vault)?;
+            vault.pool_state = Some(event.pool_state);
+            vault.total_locked = vault.total_locked.saturating_sub(event.lp_amount);
+            vault.fee_0_total = vault.fee_0_total.saturating_add(event.fee_0_amount);
+            vault.fee_1_total = vault.fee_1_total.saturating_add(event.fee_1_amount);
+            debit_lock(conn, &event.user_lock, event.lp_amount, event.timestamp)?;
+            store::put_vault(conn, &vault)?;
+        }
+        ToolLpEvent::LpFeesClaimed(event) => {
+            let mut vault = load_vault(conn, &event.vault)?;
+            vault.pool_state = Some(event.pool_state);
+            vault.total_locked = vault.total_locked.saturating_sub(event.lp_amount);
+            vault.total_permanent_locked =
+                vault.total_permanent_locked.saturating_sub(event.lp_amount);
+            vault.fee_0_total = vault.fee_0_total.saturating_add(event.fee_0_amount);
+            vault.fee_1_total = vault.fee_1_total.saturating_add(event.fee_1_amount);
+            debit_lock(conn, &event.user_lock, event.lp_amount, event.timestamp)?;
+            store::put_vault(conn, &vault)?;
+        }
+        ToolLpEvent::KeeperTipPaid(event) => {
+            let mut vault = load_vault(conn, &event.vault)?;
+            vault.keeper_tip_0_total = vault.keeper_tip_0_total.saturating_add(event.tip_0_amount);
+            vault.keeper_tip_1_total = vault.keeper_tip_1_total.saturating_add(event.tip_1_amount);
+            store::put_vault(conn, &vault)?;
+        }
+        ToolLpEvent::LockExtended(event) => {
+            set_unlock(
+                conn,
+                &event.vault,
+                &event.user,
+                event.unlock_timestamp,
+                event.timestamp,
+            )?;
+        }
+        ToolLpEvent::RolloverUpdated(event) => {
+            set_unlock(
+                conn,
+                &event.vault,
+                &event.user,
+                event.unlock_timestamp,
+                event.timestamp,
+            )?;
+        }
+        ToolLpEvent::LockRolledOver(event) => {
+            set_unlock(
+                conn,
+                &event.vault,
+                &event.user,
+                event.unlock_timestamp,
+                event.timestamp,
+            )?;
+        }
+        ToolLpEvent::LockMadePermanent(event) => {
+            let mut vault = load_vault(conn, &event.vault)?;
+            vault.total_permanent_locked = event.total_permanent_locked;
+            let mut lock = load_lock(conn, &event.vault, &event.user)?;
+            lock.is_permanent = true;
+            lock.unlock_timestamp = tool_lp::PERMANENT_UNLOCK_TIMESTAMP;
+            lock.updated_at = event.timestamp;
+            store::put_lock(conn, &lock)?;
+            store::put_vault(conn, &vault)?;
+        }
+        ToolLpEvent::LockTransferred(event) => {
+            let mut from = load_lock(conn, &event.vault, &event.from)?;
+            let mut to = load_lock(conn, &event.vault, &event.to)?;
+            from.amount = from.amount.saturating_sub(event.amount);
+            from.updated_at = event.timestamp;
+            to.amount = to.amount.saturating_add(event.amount);
+            to.unlock_timestamp = event.unlock_timestamp;
+            to.is_permanent |= from.is_permanent;
+            to.updated_at = event.timestamp;
+            store::put_lock(conn, &from)?;
+            store::put_lock(conn, &to)?;
+        }
+        ToolLpEvent::LockSplit(event) => {
+            let source = store::get_lock(conn, &event.source_lock)?;
+            let mut destination =
+                store::get_lock(conn, &event.destination_lock)?.unwrap_or_else(|| LockRow {
+                    address: event.destination_lock,
+                    vault: event.vault,
+                    user: event.recipient,
+                    ..Default::default()
+                });
+            destination.amount = destination.amount.saturating_add(event.amount);
+            destination.unlock_timestamp = event.unlock_timestamp;
+            destination.updated_at = event.timestamp;
+            if let Some(mut source) = source {
+                destination.is_permanent |= source.is_permanent;
+                source.amount = source.amount.saturating_sub(event.amount);
+                source.updated_at = event.timestamp;
+                store::put_lock(conn, &source)?;
+            }
+            store::put_lock(conn, &destination)?;
+        }
+        ToolLpEvent::LocksMerged(event) => {
+            let source = store::get_lock(conn, &event.source_lock)?;
+            let mut destination =
+                store::get_lock(conn, &event.destination_lock)?.unwrap_or_else(|| LockRow {
+                    address: event.destination_lock,
+                    vault: event.vault,
+                    user: event.owner,
+                    ..Default::default()
+                });
+            if let Some(mut source) = source {
+                // The program only merges a time lock into a permanent one, never
+                // the reverse, so the moved LP becomes permanent.
+                if destination.is_permanent && !source.is_permanent {
+                    let mut vault = load_vault(conn, &event.vault)?;
+                    vault.total_permanent_locked =
+                        vault.total_permanent_locked.saturating_add(event.amount);
+                    store::put_vault(conn, &vault)?;
+                }
+                source.amount = 0;
+                source.updated_at = event.timestamp;
+                store::put_lock(conn, &source)?;
+            }
+            destination.amount = event.total_amount;
+            destination.unlock_timestamp = event.unlock_timestamp;
+            destination.updated_at = event.timestamp;
+            store::put_lock(conn, &destination)?;
+        }
+        ToolLpEvent::LockWeightUpdated(_)
+        | ToolLpEvent::RewardAdded(_)
+        | ToolLpEvent::RewardsClaimed(_)
+        | ToolLpEvent::PositionMinted(_)
+        | ToolLpEvent::KeeperUpdated(_)
+        | ToolLpEvent::ProposalCreated(_)
+        | ToolLpEvent::VoteCast(_)
+        | ToolLpEvent::ProposalExecuted(_)
+        | ToolLpEvent::BatchWithdrawResult(_) => {}
+    }
+    Ok(())
+}
+
+fn load_vault(conn: &Connection, address: &Pubkey) -> Result<VaultRow> {
+    Ok(
+        store::get_vault(conn, address)?.unwrap_or_else(|| VaultRow {
+            address: *address,
+            ..Default::default()
+        }),
+    )
+}
+
+fn load_lock(conn: &Connection, vault: &Pubkey, user: &Pubkey) -> Result<LockRow> {
+    let address = user_lock_address(vault, user);
+    Ok(store::get_lock(conn, &address)?.unwrap_or_else(|| LockRow {
+        address,
+        vault: *vault,
+        user: *user,
+        ..Default::default()
+    }))
+}
+
+fn debit_lock(conn: &Connection, address: &Pubkey, amount: u64, timestamp: i64) -> Result<()> {
+    if let Some(mut lock) = store::get_lock(conn, address)? {
+        lock.amount = lock.amount.saturating_sub(amount);
+        lock.updated_at = timestamp;
+        store::put_lock(conn, &lock)?;
+    }
+    Ok(())
+}
+
+fn set_unlock(
+    conn: &Connection,
+    vault: &Pubkey,
+    user: &Pubkey,
+    unlock_timestamp: i64,
+    timestamp: i64,
+) -> Result<()> {
+    let mut lock = load_lock(conn, vault, user)?;
+    lock.unlock_timestamp = unlock_timestamp;
+    lock.updated_at = timestamp;
+    store::put_lock(conn, &lock)
+}
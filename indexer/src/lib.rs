@@ -0,0 +1,28 @@
+//! Lock history for tool-lp: decodes program events and replays them into a
+//! SQLite store of vaults, locks and fee totals.
+
+pub mod events;
+pub mod indexer;
+pub mod store;
+
+pub use events::{parse_logs, ToolLpEvent};
+pub use indexer::{Discrepancy, Indexer};
+pub use store::Store;
+
+pub type Result<T> = std::result::Result<T, IndexerError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum IndexerError {
+    #[error("malformed {0}")]
+    InvalidEvent(&'static str),
+    #[error("malformed event log: {0}")]
+    InvalidLog(String),
+    #[error("malformed transaction {0}")]
+    InvalidTransaction(String),
+    #[error("database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("rpc error: {0}")]
+    Rpc(#[from] solana_client::client_error::ClientError),
+    #[error(transparent)]
+    Client(#[from] tool_lp_client::ClientError),
+}
@@ -0,0 +1,247 @@
+//! SQLite schema and row access. Keys are stored as base58 text.
+
+use std::path::Path;
+
+use anchor_lang::prelude::Pubkey;
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+
+use crate::Result;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS vaults (
+    address TEXT PRIMARY KEY,
+    pool_state TEXT,
+    total_locked INTEGER NOT NULL DEFAULT 0,
+    total_permanent_locked INTEGER NOT NULL DEFAULT 0,
+    fee_0_total INTEGER NOT NULL DEFAULT 0,
+    fee_1_total INTEGER NOT NULL DEFAULT 0,
+    keeper_tip_0_total INTEGER NOT NULL DEFAULT 0,
+    keeper_tip_1_total INTEGER NOT NULL DEFAULT 0
+);
+CREATE TABLE IF NOT EXISTS locks (
+    address TEXT PRIMARY KEY,
+    vault TEXT NOT NULL,
+    user TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    unlock_timestamp INTEGER NOT NULL,
+    is_permanent INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS locks_vault ON locks (vault);
+CREATE INDEX IF NOT EXISTS locks_user ON locks (user);
+CREATE TABLE IF NOT EXISTS events (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE TABLE IF NOT EXISTS sync_state (
+    id INTEGER PRIMARY KEY CHECK (id = 0),
+    last_signature TEXT NOT NULL
+);
+";
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VaultRow {
+    pub address: Pubkey,
+    pub pool_state: Option<Pubkey>,
+    pub total_locked: u64,
+    pub total_permanent_locked: u64,
+    pub fee_0_total: u64,
+    pub fee_1_total: u64,
+    pub keeper_tip_0_total: u64,
+    pub keeper_tip_1_total: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LockRow {
+    pub address: Pubkey,
+    pub vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub unlock_timestamp: i64,
+    pub is_permanent: bool,
+    /// Timestamp of the last event that touched the lock.
+    pub updated_at: i64,
+}
+
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    pub fn transaction(&mut self) -> Result<Transaction<'_>> {
+        Ok(self.conn.transaction()?)
+    }
+
+    pub fn vaults(&self) -> Result<Vec<VaultRow>> {
+        let mut statement = self.conn.prepare("SELECT * FROM vaults ORDER BY address")?;
+        let rows = statement.query_map([], vault_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn vault(&self, address: &Pubkey) -> Result<Option<VaultRow>> {
+        get_vault(&self.conn, address)
+    }
+
+    /// Locks in `vault` that still hold LP.
+    pub fn vault_locks(&self, vault: &Pubkey) -> Result<Vec<LockRow>> {
+        let mut statement = self.conn.prepare(
+            "SELECT * FROM locks WHERE vault = ?1 AND amount > 0 ORDER BY unlock_timestamp",
+        )?;
+        let rows = statement.query_map([vault.to_string()], lock_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Locks held under `user` that still hold LP.
+    pub fn user_locks(&self, user: &Pubkey) -> Result<Vec<LockRow>> {
+        let mut statement = self.conn.prepare(
+            "SELECT * FROM locks WHERE user = ?1 AND amount > 0 ORDER BY unlock_timestamp",
+        )?;
+        let rows = statement.query_map([user.to_string()], lock_row)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn lock(&self, address: &Pubkey) -> Result<Option<LockRow>> {
+        get_lock(&self.conn, address)
+    }
+
+    /// Newest transaction signature already indexed.
+    pub fn last_signature(&self) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row("SELECT last_signature FROM sync_state", [], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+}
+
+/// Records an event, returning `false` if it was already indexed.
+pub fn insert_event(
+    conn: &Connection,
+    signature: &str,
+    event_index: usize,
+    slot: u64,
+    name: &str,
+) -> Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO events (signature, event_index, slot, name) VALUES (?1, ?2, ?3, ?4)",
+        params![signature, event_index, slot, name],
+    )?;
+    Ok(inserted > 0)
+}
+
+pub fn set_last_signature(conn: &Connection, signature: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO sync_state (id, last_signature) VALUES (0, ?1)
+         ON CONFLICT (id) DO UPDATE SET last_signature = excluded.last_signature",
+        [signature],
+    )?;
+    Ok(())
+}
+
+pub fn get_vault(conn: &Connection, address: &Pubkey) -> Result<Option<VaultRow>> {
+    Ok(conn
+        .query_row(
+            "SELECT * FROM vaults WHERE address = ?1",
+            [address.to_string()],
+            vault_row,
+        )
+        .optional()?)
+}
+
+pub fn put_vault(conn: &Connection, vault: &VaultRow) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO vaults VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            vault.address.to_string(),
+            vault.pool_state.map(|pool_state| pool_state.to_string()),
+            vault.total_locked,
+            vault.total_permanent_locked,
+            vault.fee_0_total,
+            vault.fee_1_total,
+            vault.keeper_tip_0_total,
+            vault.keeper_tip_1_total,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn get_lock(conn: &Connection, address: &Pubkey) -> Result<Option<LockRow>> {
+    Ok(conn
+        .query_row(
+            "SELECT * FROM locks WHERE address = ?1",
+            [address.to_string()],
+            lock_row,
+        )
+        .optional()?)
+}
+
+pub fn put_lock(conn: &Connection, lock: &LockRow) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO locks VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            lock.address.to_string(),
+            lock.vault.to_string(),
+            lock.user.to_string(),
+            lock.amount,
+            lock.unlock_timestamp,
+            lock.is_permanent,
+            lock.updated_at,
+        ],
+    )?;
+    Ok(())
+}
+
+fn vault_row(row: &rusqlite::Row) -> rusqlite::Result<VaultRow> {
+    let pool_state: Option<String> = row.get("pool_state")?;
+    Ok(VaultRow {
+        address: pubkey(row, "address")?,
+        pool_state: pool_state
+            .map(|pool_state| parse_pubkey(&pool_state))
+            .transpose()?,
+        total_locked: row.get("total_locked")?,
+        total_permanent_locked: row.get("total_permanent_locked")?,
+        fee_0_total: row.get("fee_0_total")?,
+        fee_1_total: row.get("fee_1_total")?,
+        keeper_tip_0_total: row.get("keeper_tip_0_total")?,
+        keeper_tip_1_total: row.get("keeper_tip_1_total")?,
+    })
+}
+
+fn lock_row(row: &rusqlite::Row) -> rusqlite::Result<LockRow> {
+    Ok(LockRow {
+        address: pubkey(row, "address")?,
+        vault: pubkey(row, "vault")?,
+        user: pubkey(row, "user")?,
+        amount: row.get("amount")?,
+        unlock_timestamp: row.get("unlock_timestamp")?,
+        is_permanent: row.get("is_permanent")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+fn pubkey(row: &rusqlite::Row, column: &str) -> rusqlite::Result<Pubkey> {
+    parse_pubkey(&row.get::<_, String>(column)?)
+}
+
+fn parse_pubkey(value: &str) -> rusqlite::Result<Pubkey> {
+    value.parse().map_err(|err| {
+        rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(err))
+    })
+}
@@ -0,0 +1,109 @@
+//! Event decoding from program logs.
+
+use anchor_lang::{prelude::Pubkey, Event};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tool_lp::{DepositEvent, WithdrawEvent};
+use tool_lp_indexer::{parse_logs, IndexerError, ToolLpEvent};
+
+fn deposit_event() -> DepositEvent {
+    DepositEvent {
+        user: Pubkey::new_unique(),
+        depositor: Pubkey::new_unique(),
+        vault: Pubkey::new_unique(),
+        pool_state: Pubkey::new_unique(),
+        amount: 1_000,
+        unlock_timestamp: 2_000,
+        timestamp: 1_000,
+    }
+}
+
+fn withdraw_event() -> WithdrawEvent {
+    WithdrawEvent {
+        user: Pubkey::new_unique(),
+        vault: Pubkey::new_unique(),
+        pool_state: Pubkey::new_unique(),
+        lp_amount: 400,
+        token_0_amount: 4_000,
+        token_1_amount: 16_000,
+        fee_0_amount: 10,
+        fee_1_amount: 0,
+        timestamp: 3_000,
+        user_lock: Pubkey::new_unique(),
+    }
+}
+
+fn program_data(event: &impl Event) -> String {
+    format!("Program data: {}", STANDARD.encode(event.data()))
+}
+
+#[test]
+fn event_is_decoded_by_its_discriminator() {
+    let event = deposit_event();
+
+    let decoded = ToolLpEvent::decode(&event.data()).unwrap();
+
+    let Some(ToolLpEvent::Deposit(decoded)) = decoded else {
+        panic!("expected a deposit event");
+    };
+    assert_eq!(decoded.user, event.user);
+    assert_eq!(decoded.vault, event.vault);
+    assert_eq!(decoded.amount, 1_000);
+    assert_eq!(decoded.unlock_timestamp, 2_000);
+}
+
+#[test]
+fn unknown_discriminator_is_not_an_event() {
+    let mut data = deposit_event().data();
+    data[0] ^= 1;
+
+    assert!(ToolLpEvent::decode(&data).unwrap().is_none());
+}
+
+#[test]
+fn truncated_event_is_rejected() {
+    let data = withdraw_event().data();
+
+    assert!(matches!(
+        ToolLpEvent::decode(&data[..data.len() - 1]),
+        Err(IndexerError::InvalidEvent("WithdrawEvent"))
+    ));
+}
+
+#[test]
+fn events_are_attributed_to_the_program_on_top_of_the_stack() {
+    let tool_lp = tool_lp::ID;
+    let other = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {tool_lp} invoke [1]"),
+        "Program log: Instruction: Withdraw".to_string(),
+        format!("Program {other} invoke [2]"),
+        // Another program may log data that happens to decode as an event.
+        program_data(&deposit_event()),
+        format!("Program {other} consumed 100 of 200000 compute units"),
+        format!("Program {other} success"),
+        program_data(&withdraw_event()),
+        format!("Program {tool_lp} consumed 200 of 200000 compute units"),
+        format!("Program {tool_lp} success"),
+        format!("Program {other} invoke [1]"),
+        program_data(&deposit_event()),
+        format!("Program {other} failed: custom program error: 0x1"),
+    ];
+
+    let events = parse_logs(&logs).unwrap();
+
+    assert_eq!(events.len(), 1);
+    assert!(matches!(events[0], ToolLpEvent::Withdraw(_)));
+}
+
+#[test]
+fn malformed_program_data_is_rejected() {
+    let logs = vec![
+        format!("Program {} invoke [1]", tool_lp::ID),
+        "Program data: not base64!".to_string(),
+    ];
+
+    assert!(matches!(
+        parse_logs(&logs),
+        Err(IndexerError::InvalidLog(_))
+    ));
+}
@@ -0,0 +1,132 @@
+//! Replaying events into an in-memory store.
+
+use anchor_lang::prelude::Pubkey;
+use tool_lp::{DepositEvent, LockMadePermanentEvent, LpFeesClaimedEvent, WithdrawEvent};
+use tool_lp_indexer::{events::user_lock_address, Indexer, Store, ToolLpEvent};
+
+const AMOUNT: u64 = 1_000;
+const UNLOCK: i64 = 2_000;
+
+struct Keys {
+    vault: Pubkey,
+    pool_state: Pubkey,
+    depositor: Pubkey,
+    user_lock: Pubkey,
+}
+
+fn keys() -> Keys {
+    let vault = Pubkey::new_unique();
+    let depositor = Pubkey::new_unique();
+    Keys {
+        vault,
+        pool_state: Pubkey::new_unique(),
+        depositor,
+        user_lock: user_lock_address(&vault, &depositor),
+    }
+}
+
+fn indexer() -> Indexer {
+    Indexer::new(Store::open_in_memory().unwrap())
+}
+
+fn deposit(keys: &Keys) -> ToolLpEvent {
+    ToolLpEvent::Deposit(DepositEvent {
+        user: keys.depositor,
+        depositor: keys.depositor,
+        vault: keys.vault,
+        pool_state: keys.pool_state,
+        amount: AMOUNT,
+        unlock_timestamp: UNLOCK,
+        timestamp: 1_000,
+    })
+}
+
+#[test]
+fn withdraw_by_a_position_holder_debits_the_lock_it_names() {
+    let keys = keys();
+    let holder = Pubkey::new_unique();
+    let mut indexer = indexer();
+    let withdraw = ToolLpEvent::Withdraw(WithdrawEvent {
+        user: holder,
+        vault: keys.vault,
+        pool_state: keys.pool_state,
+        lp_amount: AMOUNT / 4,
+        token_0_amount: 2_500,
+        token_1_amount: 10_000,
+        fee_0_amount: 25,
+        fee_1_amount: 0,
+        timestamp: UNLOCK,
+        user_lock: keys.user_lock,
+    });
+
+    let applied = indexer
+        .index_events("signature", 1, &[deposit(&keys), withdraw])
+        .unwrap();
+
+    assert_eq!(applied, 2);
+    let lock = indexer.store().lock(&keys.user_lock).unwrap().unwrap();
+    assert_eq!(lock.user, keys.depositor);
+    assert_eq!(lock.amount, AMOUNT * 3 / 4);
+    assert_eq!(lock.updated_at, UNLOCK);
+    assert!(indexer.store().user_locks(&holder).unwrap().is_empty());
+    let vault = indexer.store().vault(&keys.vault).unwrap().unwrap();
+    assert_eq!(vault.pool_state, Some(keys.pool_state));
+    assert_eq!(vault.total_locked, AMOUNT * 3 / 4);
+    assert_eq!(vault.fee_0_total, 25);
+}
+
+#[test]
+fn lp_fee_claim_debits_the_permanent_lock() {
+    let keys = keys();
+    let mut indexer = indexer();
+    let events = [
+        deposit(&keys),
+        ToolLpEvent::LockMadePermanent(LockMadePermanentEvent {
+            user: keys.depositor,
+            vault: keys.vault,
+            user_lock: keys.user_lock,
+            amount: AMOUNT,
+            total_permanent_locked: AMOUNT,
+            timestamp: 1_500,
+        }),
+        ToolLpEvent::LpFeesClaimed(LpFeesClaimedEvent {
+            user: keys.depositor,
+            vault: keys.vault,
+            pool_state: keys.pool_state,
+            lp_amount: 100,
+            token_0_amount: 1_000,
+            token_1_amount: 4_000,
+            fee_0_amount: 10,
+            fee_1_amount: 40,
+            timestamp: 3_000,
+            user_lock: keys.user_lock,
+        }),
+    ];
+
+    indexer.index_events("signature", 1, &events).unwrap();
+
+    let lock = indexer.store().lock(&keys.user_lock).unwrap().unwrap();
+    assert!(lock.is_permanent);
+    assert_eq!(lock.amount, AMOUNT - 100);
+    let vault = indexer.store().vault(&keys.vault).unwrap().unwrap();
+    assert_eq!(vault.total_locked, AMOUNT - 100);
+    assert_eq!(vault.total_permanent_locked, AMOUNT - 100);
+    assert_eq!((vault.fee_0_total, vault.fee_1_total), (10, 40));
+}
+
+#[test]
+fn replayed_transaction_is_applied_once() {
+    let keys = keys();
+    let mut indexer = indexer();
+    indexer
+        .index_events("signature", 1, &[deposit(&keys)])
+        .unwrap();
+
+    let applied = indexer
+        .index_events("signature", 1, &[deposit(&keys)])
+        .unwrap();
+
+    assert_eq!(applied, 0);
+    let vault = indexer.store().vault(&keys.vault).unwrap().unwrap();
+    assert_eq!(vault.total_locked, AMOUNT);
+}
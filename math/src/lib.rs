@@ -0,0 +1,390 @@
+//! LP valuation and performance fee math shared by the tool-lp program, its
+//! withdraw quote and off-chain clients. `no_std`, with no dependencies.
+
+#![cfg_attr(not(test), no_std)]
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+/// Fixed-point scale of the per-LP bases recorded on locks.
+pub const TOKEN_PER_LP_SCALE: u128 = 1_000_000_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MathError {
+    Overflow,
+    DivisionByZero,
+}
+
+/// One pool token's side of a withdrawal.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TokenSplit {
+    /// Pool tokens the LP is worth.
+    pub raw_amount: u64,
+    /// What the LP was worth at the lock's per-LP basis, capped at `u64::MAX`.
+    pub deposit_amount: u64,
+    /// Growth over the basis; zero when the position lost value.
+    pub growth_amount: u64,
+    /// Performance fee on the growth.
+    pub fee_amount: u64,
+    /// `raw_amount` less the fee.
+    pub payout_amount: u64,
+}
+
+/// `a * b / c`, rounded down.
+pub fn mul_div(a: u64, b: u64, c: u64) -> Result<u64, MathError> {
+    if c == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    let result = a as u128 * b as u128 / c as u128;
+    u64::try_from(result).map_err(|_| MathError::Overflow)
+}
+
+/// Pool tokens `lp_amount` of LP can be redeemed for, rounded down.
+pub fn lp_to_tokens(lp_amount: u64, reserve: u64, lp_supply: u64) -> Result<u64, MathError> {
+    mul_div(lp_amount, reserve, lp_supply)
+}
+
+/// LP that `token_amount` of one pool token is worth, rounded down.
+pub fn tokens_to_lp(token_amount: u64, reserve: u64, lp_supply: u64) -> Result<u64, MathError> {
+    mul_div(token_amount, lp_supply, reserve)
+}
+
+/// Pool tokens Raydium charges to mint `lp_amount` of LP, rounded up.
+pub fn lp_to_tokens_ceil(lp_amount: u64, reserve: u64, lp_supply: u64) -> Result<u64, MathError> {
+    if lp_supply == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    let result = (lp_amount as u128 * reserve as u128).div_ceil(lp_supply as u128);
+    u64::try_from(result).map_err(|_| MathError::Overflow)
+}
+
+/// Per-LP amount of one pool token scaled by `TOKEN_PER_LP_SCALE`, as
+/// recorded as a lock's basis. Rounded up, so no fraction of the principal is
+/// ever taken for growth.
+pub fn token_per_lp(reserve: u64, lp_supply: u64) -> Result<u128, MathError> {
+    if lp_supply == 0 {
+        return Err(MathError::DivisionByZero);
+    }
+    Ok((reserve as u128 * TOKEN_PER_LP_SCALE).div_ceil(lp_supply as u128))
+}
+
+/// Pool tokens `lp_amount` of LP was worth at the scaled per-LP `basis`,
+/// rounded up. Saturates, since callers only compare it with real amounts.
+pub fn basis_to_tokens(lp_amount: u64, basis: u128) -> u128 {
+    (lp_amount as u128)
+        .saturating_mul(basis)
+        .div_ceil(TOKEN_PER_LP_SCALE)
+}
+
+/// `bps` basis points of `amount`, rounded down.
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64, MathError> {
+    mul_div(amount, bps as u64, BPS_DENOMINATOR)
+}
+
+/// `part` of `raw`, rescaled to the `received` amount that actually arrived.
+pub fn share_of(part: u64, raw: u64, received: u64) -> Result<u64, MathError> {
+    if raw == 0 {
+        return Ok(0);
+    }
+    mul_div(part, received, raw)
+}
+
+/// Amount-weighted average of two scaled per-LP bases, rounded up so the
+/// combined LP never counts part of either principal as growth. With nothing
+/// on either side it is `basis_b`.
+pub fn blend_basis(
+    amount_a: u64,
+    basis_a: u128,
+    amount_b: u64,
+    basis_b: u128,
+) -> Result<u128, MathError> {
+    let total_amount = amount_a as u128 + amount_b as u128;
+    if total_amount == 0 {
+        return Ok(basis_b);
+    }
+    (amount_a as u128)
+        .checked_mul(basis_a)
+        .and_then(|value_a| value_a.checked_add((amount_b as u128).checked_mul(basis_b)?))
+        .map(|value| value.div_ceil(total_amount))
+        .ok_or(MathError::Overflow)
+}
+
+/// Values `lp_amount` of LP against the pool and splits it into the owner's
+/// payout and the performance fee on growth over the scaled per-LP `basis`.
+pub fn split_withdrawal(
+    lp_amount: u64,
+    reserve: u64,
+    lp_supply: u64,
+    basis: u128,
+    fee_bps: u16,
+) -> Result<TokenSplit, MathError> {
+    let raw_amount = lp_to_tokens(lp_amount, reserve, lp_supply)?;
+    let deposit_amount = basis_to_tokens(lp_amount, basis);
+    let growth_amount = (raw_amount as u128).saturating_sub(deposit_amount) as u64;
+    let fee_amount = bps_of(growth_amount, fee_bps)?;
+
+    Ok(TokenSplit {
+        raw_amount,
+        deposit_amount: u64::try_from(deposit_amount).unwrap_or(u64::MAX),
+        growth_amount,
+        fee_amount,
+        payout_amount: raw_amount - fee_amount,
+    })
+}
+
+/// LP out of `amount` that is pure growth of one pool token over the scaled
+/// per-LP `basis`. The LP left over still backs the full deposit.
+pub fn claimable_lp(
+    amount: u64,
+    basis: u128,
+    reserve: u64,
+    lp_supply: u64,
+) -> Result<u64, MathError> {
+    if reserve == 0 {
+        return Ok(0);
+    }
+    // The whole supply valued at the basis, against what it holds now.
+    let principal = basis_to_tokens(lp_supply, basis);
+    let growth = (reserve as u128).saturating_sub(principal);
+    // `growth < reserve`, so the result is below `amount`.
+    Ok((amount as u128 * growth / reserve as u128) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn mul_div_rounds_down() {
+        assert_eq!(mul_div(10, 3, 4), Ok(7));
+        assert_eq!(mul_div(u64::MAX, u64::MAX, u64::MAX), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn mul_div_reports_errors() {
+        assert_eq!(mul_div(1, 1, 0), Err(MathError::DivisionByZero));
+        assert_eq!(mul_div(u64::MAX, 2, 1), Err(MathError::Overflow));
+    }
+
+    #[test]
+    fn lp_to_tokens_ceil_rounds_up() {
+        assert_eq!(lp_to_tokens_ceil(3, 10, 4), Ok(8));
+        assert_eq!(lp_to_tokens_ceil(4, 10, 4), Ok(10));
+        assert_eq!(lp_to_tokens_ceil(1, 1, 0), Err(MathError::DivisionByZero));
+    }
+
+    const BASIS_40: u128 = 40 * TOKEN_PER_LP_SCALE;
+
+    #[test]
+    fn token_per_lp_keeps_fractions() {
+        assert_eq!(token_per_lp(1_500, 1_000), Ok(1_500_000_000_000));
+        assert_eq!(token_per_lp(1, 3), Ok(333_333_333_334));
+        assert_eq!(token_per_lp(1, 0), Err(MathError::DivisionByZero));
+    }
+
+    #[test]
+    fn split_charges_fee_on_growth_only() {
+        // 100 LP of a 1_000 LP pool holding 50_000 tokens, deposited at 40 per LP.
+        let split = split_withdrawal(100, 50_000, 1_000, BASIS_40, 2_000).unwrap();
+        assert_eq!(
+            split,
+            TokenSplit {
+                raw_amount: 5_000,
+                deposit_amount: 4_000,
+                growth_amount: 1_000,
+                fee_amount: 200,
+                payout_amount: 4_800,
+            }
+        );
+    }
+
+    #[test]
+    fn split_without_growth_is_free() {
+        let split = split_withdrawal(100, 30_000, 1_000, BASIS_40, 2_000).unwrap();
+        assert_eq!(split.growth_amount, 0);
+        assert_eq!(split.fee_amount, 0);
+        assert_eq!(split.payout_amount, split.raw_amount);
+    }
+
+    #[test]
+    fn split_with_fractional_basis_charges_no_fee_without_growth() {
+        // 1.5 tokens per LP; a whole-token basis of 1 would see a third as growth.
+        let basis = token_per_lp(1_500, 1_000).unwrap();
+        let split = split_withdrawal(100, 1_500, 1_000, basis, 2_000).unwrap();
+        assert_eq!(split.raw_amount, 150);
+        assert_eq!(split.deposit_amount, 150);
+        assert_eq!(split.fee_amount, 0);
+    }
+
+    #[test]
+    fn split_with_huge_basis_does_not_overflow() {
+        let split = split_withdrawal(u64::MAX, 1, 1, u128::MAX, 5_000).unwrap();
+        assert_eq!(split.deposit_amount, u64::MAX);
+        assert_eq!(split.fee_amount, 0);
+    }
+
+    #[test]
+    fn share_of_empty_raw_is_zero() {
+        assert_eq!(share_of(10, 0, 10), Ok(0));
+    }
+
+    #[test]
+    fn blend_basis_weights_by_amount() {
+        assert_eq!(blend_basis(300, 10, 100, 50), Ok(20));
+        // Rounded up: (1 * 1 + 2 * 2) / 3.
+        assert_eq!(blend_basis(1, 1, 2, 2), Ok(2));
+        assert_eq!(blend_basis(0, 10, 100, 50), Ok(50));
+        assert_eq!(blend_basis(0, 10, 0, 50), Ok(50));
+        assert_eq!(
+            blend_basis(u64::MAX, u128::MAX, 1, 0),
+            Err(MathError::Overflow)
+        );
+    }
+
+    #[test]
+    fn claimable_lp_keeps_principal() {
+        // Reserve doubled since the deposit at 40 per LP: half the LP is growth.
+        assert_eq!(claimable_lp(100, BASIS_40, 80_000, 1_000), Ok(50));
+        assert_eq!(claimable_lp(100, BASIS_40, 40_000, 1_000), Ok(0));
+    }
+
+    #[test]
+    fn claimable_lp_with_fractional_basis_keeps_principal() {
+        // Deposited at 1.5 tokens per LP. Without growth nothing is claimable,
+        // and once the reserve doubles half the LP is.
+        let basis = token_per_lp(1_500, 1_000).unwrap();
+        assert_eq!(claimable_lp(100, basis, 1_500, 1_000), Ok(0));
+        assert_eq!(claimable_lp(100, basis, 3_000, 1_000), Ok(50));
+    }
+
+    #[test]
+    fn claimable_lp_rebased_at_the_same_price_is_zero() {
+        // Re-basing after a claim at the current price leaves nothing to claim.
+        let basis = token_per_lp(1_000, 3).unwrap();
+        assert_eq!(claimable_lp(1, basis, 1_000, 3), Ok(0));
+        assert_eq!(claimable_lp(u64::MAX, basis, 1_000, 3), Ok(0));
+    }
+
+    proptest! {
+        #[test]
+        fn mul_div_matches_wide_arithmetic(a: u64, b: u64, c in 1u64..) {
+            let wide = a as u128 * b as u128 / c as u128;
+            match mul_div(a, b, c) {
+                Ok(result) => prop_assert_eq!(result as u128, wide),
+                Err(err) => {
+                    prop_assert_eq!(err, MathError::Overflow);
+                    prop_assert!(wide > u64::MAX as u128);
+                }
+            }
+        }
+
+        #[test]
+        fn lp_to_tokens_never_overpays(
+            lp_amount: u64,
+            reserve: u64,
+            lp_supply in 1u64..,
+        ) {
+            if let Ok(tokens) = lp_to_tokens(lp_amount, reserve, lp_supply) {
+                prop_assert!(
+                    tokens as u128 * lp_supply as u128 <= lp_amount as u128 * reserve as u128
+                );
+            }
+        }
+
+        #[test]
+        fn lp_bought_with_tokens_costs_no_more(
+            token_amount: u64,
+            reserve in 1u64..,
+            lp_supply in 1u64..,
+        ) {
+            if let Ok(lp_amount) = tokens_to_lp(token_amount, reserve, lp_supply) {
+                let cost = lp_to_tokens_ceil(lp_amount, reserve, lp_supply).unwrap();
+                prop_assert!(cost <= token_amount);
+            }
+        }
+
+        #[test]
+        fn split_is_conserved(
+            lp_amount: u64,
+            reserve: u64,
+            lp_supply in 1u64..,
+            basis: u128,
+            fee_bps in 0u16..=10_000,
+        ) {
+            if let Ok(split) = split_withdrawal(lp_amount, reserve, lp_supply, basis, fee_bps)
+            {
+                prop_assert_eq!(split.payout_amount + split.fee_amount, split.raw_amount);
+                prop_assert!(split.growth_amount <= split.raw_amount);
+                prop_assert!(split.fee_amount <= split.growth_amount);
+                prop_assert!(
+                    split.fee_amount as u128 * BPS_DENOMINATOR as u128
+                        <= split.growth_amount as u128 * fee_bps as u128
+                );
+            }
+        }
+
+        #[test]
+        fn split_fee_is_monotonic_in_fee_bps(
+            lp_amount: u64,
+            reserve: u64,
+            lp_supply in 1u64..,
+            basis: u128,
+            fee_bps in 0u16..10_000,
+        ) {
+            let lower = split_withdrawal(lp_amount, reserve, lp_supply, basis, fee_bps);
+            let higher = split_withdrawal(lp_amount, reserve, lp_supply, basis, fee_bps + 1);
+            if let (Ok(lower), Ok(higher)) = (lower, higher) {
+                prop_assert!(lower.fee_amount <= higher.fee_amount);
+            }
+        }
+
+        #[test]
+        fn share_of_stays_within_received(part: u64, raw: u64, received: u64) {
+            prop_assume!(part <= raw && received <= raw);
+            let share = share_of(part, raw, received).unwrap();
+            prop_assert!(share <= received);
+            prop_assert!(share <= part);
+        }
+
+        #[test]
+        fn claimable_lp_leaves_deposit_backed(
+            amount: u64,
+            basis in 0u128..=u64::MAX as u128 * TOKEN_PER_LP_SCALE,
+            reserve: u64,
+            lp_supply in 1u64..,
+        ) {
+            let claimable = claimable_lp(amount, basis, reserve, lp_supply).unwrap();
+            prop_assert!(claimable <= amount);
+            if claimable > 0 {
+                // What the remaining LP redeems for covers the deposit at its basis.
+                let remaining = (amount - claimable) as u128;
+                let principal = basis_to_tokens(lp_supply, basis);
+                prop_assert!(remaining * reserve as u128 >= amount as u128 * principal);
+            }
+        }
+
+        #[test]
+        fn blend_basis_stays_between_its_inputs(
+            amount_a: u64,
+            basis_a in 0u128..=u64::MAX as u128 * TOKEN_PER_LP_SCALE,
+            amount_b: u64,
+            basis_b in 0u128..=u64::MAX as u128 * TOKEN_PER_LP_SCALE,
+        ) {
+            if let Ok(blended) = blend_basis(amount_a, basis_a, amount_b, basis_b) {
+                prop_assert!(blended >= basis_a.min(basis_b));
+                prop_assert!(blended <= basis_a.max(basis_b));
+            }
+        }
+
+        #[test]
+        fn token_per_lp_basis_covers_the_redeemable_amount(
+            lp_amount: u64,
+            reserve: u64,
+            lp_supply in 1u64..,
+        ) {
+            let basis = token_per_lp(reserve, lp_supply).unwrap();
+            if let Ok(tokens) = lp_to_tokens(lp_amount, reserve, lp_supply) {
+                prop_assert!(basis_to_tokens(lp_amount, basis) >= tokens as u128);
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use raydium_cp_swap::states::PoolState;
+
+use crate::{Config, PoolAllowlistEntry, CONFIG_SEED, POOL_ALLOWLIST_SEED};
+
+pub fn handler(ctx: Context<AddPoolToAllowlist>) -> Result<()> {
+    let entry = &mut ctx.accounts.pool_allowlist;
+
+    entry.pool_state = ctx.accounts.pool_state.key();
+    entry.bump = ctx.bumps.pool_allowlist;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddPoolToAllowlist<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub pool_state: AccountLoader<'info, PoolState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + PoolAllowlistEntry::LEN,
+        seeds = [POOL_ALLOWLIST_SEED, pool_state.key().as_ref()],
+        bump
+    )]
+    pub pool_allowlist: Account<'info, PoolAllowlistEntry>,
+    pub system_program: Program<'info, System>,
+}
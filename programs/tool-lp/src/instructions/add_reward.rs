@@ -0,0 +1,134 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{
+    utils::get_transfer_inverse_fee, Config, Error, RewardAddedEvent, RewardInfo, Vault,
+    CONFIG_SEED, REWARD_VAULT_SEED, VAULT_AUTHORITY_SEED,
+};
+
+pub fn handler(
+    ctx: Context<AddReward>,
+    emissions_per_second: u64,
+    start_timestamp: i64,
+    end_timestamp: i64,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let authority = ctx.accounts.authority.key();
+    let reward_mint = ctx.accounts.reward_mint.key();
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        authority == vault.creator || authority == ctx.accounts.config.admin,
+        Error::Unauthorized
+    );
+    require!(
+        emissions_per_second > 0
+            && start_timestamp >= current_timestamp
+            && end_timestamp > start_timestamp,
+        Error::InvalidInput
+    );
+    require!(
+        !vault.rewards.iter().any(|reward| reward.is_initialized()
+            && !reward.is_expired(current_timestamp)
+            && reward.mint == reward_mint),
+        Error::DuplicateReward
+    );
+    // An expired stream of the same mint is replaced in place, since it owns
+    // this mint's reward vault. Otherwise a free slot goes before an expired one.
+    let reward_index = vault
+        .rewards
+        .iter()
+        .position(|reward| reward.is_expired(current_timestamp) && reward.mint == reward_mint)
+        .or_else(|| {
+            vault
+                .rewards
+                .iter()
+                .position(|reward| !reward.is_initialized())
+        })
+        .or_else(|| {
+            vault
+                .rewards
+                .iter()
+                .position(|reward| reward.is_expired(current_timestamp))
+        })
+        .ok_or(Error::RewardSlotsFull)?;
+
+    let total_emissions = (emissions_per_second as u128)
+        .checked_mul((end_timestamp - start_timestamp) as u128)
+        .and_then(|amount| u64::try_from(amount).ok())
+        .ok_or(Error::ArithmeticError)?;
+    let transfer_fee = get_transfer_inverse_fee(&ctx.accounts.reward_mint, total_emissions)?;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.reward_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.funder_token_account.to_account_info(),
+                to: ctx.accounts.reward_vault.to_account_info(),
+                mint: ctx.accounts.reward_mint.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+            },
+        ),
+        total_emissions
+            .checked_add(transfer_fee)
+            .ok_or(Error::ArithmeticError)?,
+        ctx.accounts.reward_mint.decimals,
+    )?;
+
+    // Bring existing streams up to date before the new one starts counting.
+    vault.update_rewards(current_timestamp)?;
+    vault.rewards[reward_index] = RewardInfo {
+        mint: reward_mint,
+        reward_vault: ctx.accounts.reward_vault.key(),
+        emissions_per_second,
+        start_timestamp,
+        end_timestamp,
+        last_update_timestamp: start_timestamp,
+        reward_per_weight: 0,
+    };
+    vault.reward_generations[reward_index] = vault.reward_generations[reward_index].wrapping_add(1);
+    vault.undistributed_rewards[reward_index] = 0;
+
+    emit!(RewardAddedEvent {
+        vault: vault.key(),
+        reward_mint,
+        reward_index: reward_index as u8,
+        emissions_per_second,
+        start_timestamp,
+        end_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AddReward<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    /// Already exists when replacing an expired stream of the same mint
+    #[account(
+        init_if_needed,
+        payer = authority,
+        token::mint = reward_mint,
+        token::authority = vault_authority,
+        token::token_program = reward_token_program,
+        seeds = [REWARD_VAULT_SEED, vault.key().as_ref(), reward_mint.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = reward_mint, token::authority = authority)]
+    pub funder_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, vault.pool_state.as_ref(), vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    /// CHECK: PDA verified via seeds
+    pub vault_authority: UncheckedAccount<'info>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeSet;
+
+use anchor_lang::prelude::*;
+
+use crate::{
+    BatchWithdrawResultEvent, Error, Withdraw, WithdrawBumps, WithdrawQuote,
+    WITHDRAW_ACCOUNTS_PER_POSITION, WITHDRAW_SHARED_ACCOUNTS,
+};
+
+/// Withdraws the full balance of several unlocked positions in one vault.
+/// The remaining accounts start with the `WITHDRAW_SHARED_ACCOUNTS` accounts
+/// of `Withdraw` that do not depend on the lock, in `Withdraw` order, followed
+/// by `WITHDRAW_ACCOUNTS_PER_POSITION` accounts for each position. Omitted
+/// optional accounts are passed as this program's id. With `skip_failed`,
+/// positions that cannot be withdrawn are reported and skipped instead of
+/// failing the whole batch.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchWithdraw<'info>>,
+    skip_failed: bool,
+) -> Result<()> {
+    let remaining_accounts = ctx.remaining_accounts;
+    require!(
+        remaining_accounts.len() > WITHDRAW_SHARED_ACCOUNTS
+            && (remaining_accounts.len() - WITHDRAW_SHARED_ACCOUNTS)
+                % WITHDRAW_ACCOUNTS_PER_POSITION
+                == 0,
+        Error::InvalidInput
+    );
+    let (shared_accounts, positions) = remaining_accounts.split_at(WITHDRAW_SHARED_ACCOUNTS);
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let signer = ctx.accounts.user.key();
+
+    for (index, position_accounts) in positions.chunks(WITHDRAW_ACCOUNTS_PER_POSITION).enumerate() {
+        let user_lock = position_accounts[0].key();
+        let withdraw_accounts = withdraw_accounts(shared_accounts, position_accounts);
+
+        // Everything that can fail is checked before the Raydium CPI, since a
+        // failed CPI cannot be recovered from.
+        match prepare(ctx.program_id, withdraw_accounts, signer, current_timestamp) {
+            Ok(mut prepared) => {
+                prepared.accounts.complete(
+                    prepared.lp_token_amount,
+                    &prepared.quote,
+                    prepared.owner,
+                    prepared.keeper_tip_bps,
+                    current_timestamp,
+                )?;
+                // Persist this position before the next one, which shares its vault.
+                prepared.accounts.exit(ctx.program_id)?;
+
+                emit!(BatchWithdrawResultEvent {
+                    index: index as u16,
+                    user_lock,
+                    success: true,
+                    error_code: 0,
+                });
+            }
+            Err(err) if skip_failed => {
+                emit!(BatchWithdrawResultEvent {
+                    index: index as u16,
+                    user_lock,
+                    success: false,
+                    error_code: u64::from(ProgramError::from(err)),
+                });
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Puts one position's accounts between the shared ones, in `Withdraw` order.
+/// The list has to outlive the handler for `Withdraw::try_accounts`; the
+/// program's bump allocator never frees memory, so leaking it costs nothing
+/// more than dropping it.
+fn withdraw_accounts<'info>(
+    shared_accounts: &[AccountInfo<'info>],
+    position_accounts: &[AccountInfo<'info>],
+) -> &'info [AccountInfo<'info>] {
+    let mut accounts =
+        Vec::with_capacity(WITHDRAW_SHARED_ACCOUNTS + WITHDRAW_ACCOUNTS_PER_POSITION);
+    // vault, user
+    accounts.extend_from_slice(&shared_accounts[..2]);
+    // user_lock, position_token_account, position_mint
+    accounts.extend_from_slice(&position_accounts[..3]);
+    // vault_token_account, vault_token_0_account, vault_token_1_account
+    accounts.extend_from_slice(&shared_accounts[2..5]);
+    // user_token_0_account, user_token_1_account
+    accounts.extend_from_slice(&position_accounts[3..]);
+    accounts.extend_from_slice(&shared_accounts[5..]);
+    accounts.leak()
+}
+
+struct PreparedWithdraw<'info> {
+    accounts: Box<Withdraw<'info>>,
+    lp_token_amount: u64,
+    owner: Pubkey,
+    keeper_tip_bps: u16,
+    quote: WithdrawQuote,
+}
+
+fn prepare<'info>(
+    program_id: &Pubkey,
+    mut position_accounts: &'info [AccountInfo<'info>],
+    signer: Pubkey,
+    current_timestamp: i64,
+) -> Result<PreparedWithdraw<'info>> {
+    let accounts = Box::new(Withdraw::try_accounts(
+        program_id,
+        &mut position_accounts,
+        &[],
+        &mut WithdrawBumps::default(),
+        &mut BTreeSet::new(),
+    )?);
+    require_keys_eq!(accounts.user.key(), signer, Error::Unauthorized);
+
+    let lp_token_amount = accounts.user_lock.amount;
+    let (owner, keeper_tip_bps) = accounts.authorize(lp_token_amount, current_timestamp)?;
+    let quote = accounts.quote(
+        lp_token_amount,
+        accounts.user_lock.deposit_basis_0,
+        accounts.user_lock.deposit_basis_1,
+    )?;
+
+    Ok(PreparedWithdraw {
+        accounts,
+        lp_token_amount,
+        owner,
+        keeper_tip_bps,
+        quote,
+    })
+}
+
+#[derive(Accounts)]
+pub struct BatchWithdraw<'info> {
+    /// Signs as `user` for every position in the batch
+    pub user: Signer<'info>,
+}
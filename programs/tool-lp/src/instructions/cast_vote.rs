@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{
+    Error, Proposal, UserLock, VoteCastEvent, VoteRecord, USER_LOCK_SEED, VOTE_RECORD_SEED,
+};
+
+pub fn handler(ctx: Context<CastVote>, support: bool) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    let user_lock = &ctx.accounts.user_lock;
+    let vote_record = &mut ctx.accounts.vote_record;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    user_lock.assert_owner(
+        &ctx.accounts.voter.key(),
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+    require!(
+        current_timestamp < proposal.voting_ends_at,
+        Error::VotingClosed
+    );
+    require!(user_lock.amount > 0, Error::InsufficientBalance);
+    let votes = user_lock.votes_at(proposal.snapshot_timestamp);
+    require!(votes > 0, Error::LockAfterSnapshot);
+
+    if support {
+        proposal.votes_for = proposal
+            .votes_for
+            .checked_add(votes)
+            .ok_or(Error::ArithmeticError)?;
+    } else {
+        proposal.votes_against = proposal
+            .votes_against
+            .checked_add(votes)
+            .ok_or(Error::ArithmeticError)?;
+    }
+
+    vote_record.proposal = proposal.key();
+    vote_record.user_lock = user_lock.key();
+    vote_record.voter = ctx.accounts.voter.key();
+    vote_record.votes = votes;
+    vote_record.support = support;
+
+    emit!(VoteCastEvent {
+        proposal: proposal.key(),
+        voter: vote_record.voter,
+        votes,
+        support,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CastVote<'info> {
+    #[account(mut)]
+    pub proposal: Account<'info, Proposal>,
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    #[account(
+        seeds = [USER_LOCK_SEED, proposal.vault.as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + VoteRecord::LEN,
+        seeds = [VOTE_RECORD_SEED, proposal.key().as_ref(), user_lock.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,88 @@
+use anchor_lang::prelude::*;
+
+use crate::{math, utils::amount_after_transfer_fee, Error, LpFeesClaimedEvent, Withdraw};
+
+/// Claims the fee growth of a permanent lock by withdrawing the LP it is worth.
+/// Uses the same accounts as `withdraw`.
+pub fn handler(ctx: Context<Withdraw>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    accounts.user_lock.assert_owner(
+        &accounts.user.key(),
+        accounts.position_token_account.as_deref(),
+    )?;
+    require!(accounts.user_lock.is_permanent, Error::LockNotPermanent);
+
+    let (vault_0_amount, vault_1_amount, lp_supply) = accounts.pool_amounts()?;
+    let lp_token_amount = math::claimable_lp(
+        accounts.user_lock.amount,
+        accounts.user_lock.deposit_basis_0,
+        vault_0_amount,
+        lp_supply,
+    )
+    .map_err(Error::from)?
+    .min(
+        math::claimable_lp(
+            accounts.user_lock.amount,
+            accounts.user_lock.deposit_basis_1,
+            vault_1_amount,
+            lp_supply,
+        )
+        .map_err(Error::from)?,
+    );
+    require!(lp_token_amount > 0, Error::NoFeesToClaim);
+    require!(
+        lp_token_amount < accounts.user_lock.amount,
+        Error::InsufficientBalance
+    );
+
+    // Everything claimed is growth, so the performance fee applies to all of it.
+    let quote = accounts.quote(lp_token_amount, 0, 0)?;
+    let owner = accounts.user.key();
+    let payout = accounts.execute(lp_token_amount, &quote, owner, 0)?;
+
+    let vault = &mut accounts.vault;
+    let user_lock = &mut accounts.user_lock;
+    vault.update_rewards(current_timestamp)?;
+    user_lock.settle_rewards(vault, current_timestamp)?;
+
+    user_lock.amount = user_lock
+        .amount
+        .checked_sub(lp_token_amount)
+        .ok_or(Error::ArithmeticError)?;
+    user_lock.checkpoint_votes(current_timestamp);
+    vault.total_locked = vault
+        .total_locked
+        .checked_sub(lp_token_amount)
+        .ok_or(Error::ArithmeticError)?;
+    vault.total_permanent_locked = vault
+        .total_permanent_locked
+        .checked_sub(lp_token_amount)
+        .ok_or(Error::ArithmeticError)?;
+
+    // The remaining LP is worth at least the original deposit in both tokens.
+    // Re-basing at the current price, rounded up, keeps it from being claimed
+    // a second time.
+    user_lock.set_basis(
+        math::token_per_lp(vault_0_amount, lp_supply).map_err(Error::from)?,
+        math::token_per_lp(vault_1_amount, lp_supply).map_err(Error::from)?,
+    );
+    user_lock.refresh_weight(vault, current_timestamp)?;
+    user_lock.reset_reward_debts(vault)?;
+
+    emit!(LpFeesClaimedEvent {
+        user: accounts.user.key(),
+        vault: vault.key(),
+        pool_state: vault.pool_state,
+        lp_amount: lp_token_amount,
+        token_0_amount: amount_after_transfer_fee(&accounts.vault_0_mint, payout.token_0_amount)?,
+        token_1_amount: amount_after_transfer_fee(&accounts.vault_1_mint, payout.token_1_amount)?,
+        fee_0_amount: amount_after_transfer_fee(&accounts.vault_0_mint, payout.fee_0_amount)?,
+        fee_1_amount: amount_after_transfer_fee(&accounts.vault_1_mint, payout.fee_1_amount)?,
+        timestamp: current_timestamp,
+        user_lock: user_lock.key(),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::{Error, RewardsClaimedEvent, UserLock, Vault, USER_LOCK_SEED, VAULT_AUTHORITY_SEED};
+
+pub fn handler(ctx: Context<ClaimRewards>, reward_index: u8) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let user_lock = &mut ctx.accounts.user_lock;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let reward_index = reward_index as usize;
+
+    user_lock.assert_owner(
+        &ctx.accounts.user.key(),
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+
+    require!(
+        reward_index < vault.rewards.len(),
+        Error::InvalidRewardIndex
+    );
+    let reward = vault.rewards[reward_index];
+    require!(reward.is_initialized(), Error::InvalidRewardIndex);
+    require!(
+        ctx.accounts.reward_mint.key() == reward.mint
+            && ctx.accounts.reward_vault.key() == reward.reward_vault,
+        Error::InvalidInput
+    );
+
+    vault.update_rewards(current_timestamp)?;
+    user_lock.settle_rewards(vault, current_timestamp)?;
+    user_lock.refresh_weight(vault, current_timestamp)?;
+    user_lock.reset_reward_debts(vault)?;
+
+    let amount = user_lock.rewards_owed[reward_index];
+    user_lock.rewards_owed[reward_index] = 0;
+
+    if amount > 0 {
+        let vault_key = vault.key();
+        let seeds = &[
+            VAULT_AUTHORITY_SEED,
+            vault.pool_state.as_ref(),
+            vault_key.as_ref(),
+            &[vault.authority_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.reward_token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.reward_vault.to_account_info(),
+                    to: ctx.accounts.user_reward_account.to_account_info(),
+                    mint: ctx.accounts.reward_mint.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            ctx.accounts.reward_mint.decimals,
+        )?;
+    }
+
+    emit!(RewardsClaimedEvent {
+        user: ctx.accounts.user.key(),
+        vault: vault.key(),
+        reward_mint: reward.mint,
+        amount,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+    #[account(mut, token::mint = reward_mint, token::authority = vault_authority)]
+    pub reward_vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut, token::mint = reward_mint)]
+    pub user_reward_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, vault.pool_state.as_ref(), vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    /// CHECK: PDA verified via seeds
+    pub vault_authority: UncheckedAccount<'info>,
+    pub reward_token_program: Interface<'info, TokenInterface>,
+}
@@ -0,0 +1,357 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{self, Allocate, Assign, CreateAccount, Transfer},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, InitializeAccount3, Token, TransferChecked},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use raydium_cp_swap::{cpi, program::RaydiumCpSwap};
+
+use crate::{
+    math, Config, DepositEvent, Error, LockPolicy, PoolAllowlistEntry, UserLock, Vault,
+    CONFIG_SEED, POOL_ALLOWLIST_SEED, USER_LOCK_SEED, VAULT_AUTHORITY_SEED, VAULT_SEED,
+    VAULT_TOKEN_SEED,
+};
+
+pub fn handler(
+    ctx: Context<CreatePoolAndLock>,
+    init_amount_0: u64,
+    init_amount_1: u64,
+    open_time: u64,
+    lock_policy: LockPolicy,
+    unlock_timestamp: i64,
+) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    if !Config::allows_permissionless_vaults(&ctx.accounts.config)? {
+        require!(
+            ctx.accounts.pool_allowlist.is_some(),
+            Error::PoolNotAllowlisted
+        );
+    }
+    lock_policy.validate()?;
+
+    cpi::initialize(
+        CpiContext::new(
+            ctx.accounts.cp_swap_program.to_account_info(),
+            cpi::accounts::Initialize {
+                creator: ctx.accounts.creator.to_account_info(),
+                amm_config: ctx.accounts.amm_config.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+                pool_state: ctx.accounts.pool_state.to_account_info(),
+                token_0_mint: ctx.accounts.token_0_mint.to_account_info(),
+                token_1_mint: ctx.accounts.token_1_mint.to_account_info(),
+                lp_mint: ctx.accounts.lp_mint.to_account_info(),
+                creator_token_0: ctx.accounts.creator_token_0.to_account_info(),
+                creator_token_1: ctx.accounts.creator_token_1.to_account_info(),
+                creator_lp_token: ctx.accounts.creator_lp_token.to_account_info(),
+                token_0_vault: ctx.accounts.token_0_vault.to_account_info(),
+                token_1_vault: ctx.accounts.token_1_vault.to_account_info(),
+                create_pool_fee: ctx.accounts.create_pool_fee.to_account_info(),
+                observation_state: ctx.accounts.observation_state.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                token_0_program: ctx.accounts.token_0_program.to_account_info(),
+                token_1_program: ctx.accounts.token_1_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            },
+        ),
+        init_amount_0,
+        init_amount_1,
+        open_time,
+    )?;
+
+    // The LP mint only exists now, so the vault's LP account is created by hand
+    // at the same address `initialize_vault` would use.
+    let pool_state_key = ctx.accounts.pool_state.key();
+    let vault_key = ctx.accounts.vault.key();
+    let vault_token_seeds = &[
+        VAULT_TOKEN_SEED,
+        pool_state_key.as_ref(),
+        vault_key.as_ref(),
+        &[ctx.bumps.vault_token_account],
+    ];
+    create_pda_account(
+        &ctx.accounts.creator.to_account_info(),
+        &ctx.accounts.vault_token_account.to_account_info(),
+        &ctx.accounts.system_program.to_account_info(),
+        &[&vault_token_seeds[..]],
+        token::TokenAccount::LEN,
+        &token::ID,
+    )?;
+    token::initialize_account3(CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        InitializeAccount3 {
+            account: ctx.accounts.vault_token_account.to_account_info(),
+            mint: ctx.accounts.lp_mint.to_account_info(),
+            authority: ctx.accounts.vault_authority.to_account_info(),
+        },
+    ))?;
+
+    for (vault_token_account, mint, token_program) in [
+        (
+            &ctx.accounts.vault_token_0_account,
+            &ctx.accounts.token_0_mint,
+            &ctx.accounts.token_0_program,
+        ),
+        (
+            &ctx.accounts.vault_token_1_account,
+            &ctx.accounts.token_1_mint,
+            &ctx.accounts.token_1_program,
+        ),
+    ] {
+        if vault_token_account.to_account_info().data_is_empty() {
+            anchor_spl::associated_token::create(CpiContext::new(
+                ctx.accounts.associated_token_program.to_account_info(),
+                anchor_spl::associated_token::Create {
+                    payer: ctx.accounts.creator.to_account_info(),
+                    associated_token: vault_token_account.to_account_info(),
+                    authority: ctx.accounts.vault_authority.to_account_info(),
+                    mint: mint.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    token_program: token_program.to_account_info(),
+                },
+            ))?;
+        }
+    }
+
+    let lp_mint = Mint::try_deserialize(&mut &ctx.accounts.lp_mint.try_borrow_data()?[..])?;
+    let amount = token_amount(&ctx.accounts.creator_lp_token)?;
+    let vault_0_amount = token_amount(&ctx.accounts.token_0_vault)?;
+    let vault_1_amount = token_amount(&ctx.accounts.token_1_vault)?;
+
+    // The whole initial LP goes into the lock.
+    token::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.creator_lp_token.to_account_info(),
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                to: ctx.accounts.vault_token_account.to_account_info(),
+                authority: ctx.accounts.creator.to_account_info(),
+            },
+        ),
+        amount,
+        lp_mint.decimals,
+    )?;
+
+    let vault = &mut ctx.accounts.vault;
+    vault.initialize(
+        pool_state_key,
+        ctx.accounts.lp_mint.key(),
+        ctx.accounts.vault_token_account.key(),
+        ctx.accounts.creator.key(),
+        ctx.bumps.vault,
+        ctx.bumps.vault_authority,
+    );
+    vault.set_lock_policy(lock_policy);
+
+    require!(
+        amount > 0 && amount >= vault.lock_policy.min_deposit,
+        Error::DepositTooSmall
+    );
+    let user_lock = &mut ctx.accounts.user_lock;
+    user_lock.check_deposit_terms(vault, true, unlock_timestamp, current_timestamp)?;
+
+    // A new pool has no accrued protocol or fund fees yet. The pool's own
+    // `lp_supply` also counts the few LP Raydium never mints, so basing on the
+    // mint supply rounds the basis up by a negligible amount.
+    let deposit_basis_0 =
+        math::token_per_lp(vault_0_amount, lp_mint.supply).map_err(Error::from)?;
+    let deposit_basis_1 =
+        math::token_per_lp(vault_1_amount, lp_mint.supply).map_err(Error::from)?;
+
+    user_lock.user = ctx.accounts.creator.key();
+    user_lock.vault = vault_key;
+    user_lock.credit(
+        vault,
+        amount,
+        unlock_timestamp,
+        deposit_basis_0,
+        deposit_basis_1,
+        current_timestamp,
+    )?;
+
+    emit!(DepositEvent {
+        user: ctx.accounts.creator.key(),
+        depositor: ctx.accounts.creator.key(),
+        vault: vault.key(),
+        pool_state: vault.pool_state,
+        amount,
+        unlock_timestamp,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Creates `account` at a PDA of this program. The address is predictable, so
+/// anyone can send it lamports first, which makes a plain `create_account`
+/// fail; a funded address is topped up, allocated and assigned instead.
+fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    space: usize,
+    owner: &Pubkey,
+) -> Result<()> {
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    if current_lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                signer_seeds,
+            ),
+            required_lamports,
+            space as u64,
+            owner,
+        );
+    }
+
+    let top_up = required_lamports.saturating_sub(current_lamports);
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: account.clone(),
+            },
+            signer_seeds,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Assign {
+                account_to_assign: account.clone(),
+            },
+            signer_seeds,
+        ),
+        owner,
+    )
+}
+
+fn token_amount(account: &AccountInfo) -> Result<u64> {
+    let token_account = TokenAccount::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    Ok(token_account.amount)
+}
+
+#[derive(Accounts)]
+pub struct CreatePoolAndLock<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    /// CHECK: Program config; may not be initialized yet, see `Config::allows_permissionless_vaults`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+    /// Required unless the config allows permissionless vaults
+    #[account(
+        seeds = [POOL_ALLOWLIST_SEED, pool_state.key().as_ref()],
+        bump = pool_allowlist.bump
+    )]
+    pub pool_allowlist: Option<Box<Account<'info, PoolAllowlistEntry>>>,
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Vault::LEN,
+        seeds = [VAULT_SEED, pool_state.key().as_ref()],
+        bump
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + UserLock::LEN,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), creator.key().as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// CHECK: Created after the pool, once the LP mint exists
+    #[account(
+        mut,
+        seeds = [VAULT_TOKEN_SEED, pool_state.key().as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: UncheckedAccount<'info>,
+    /// CHECK: Will be created if empty
+    #[account(mut)]
+    pub vault_token_0_account: UncheckedAccount<'info>,
+    /// CHECK: Will be created if empty
+    #[account(mut)]
+    pub vault_token_1_account: UncheckedAccount<'info>,
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, pool_state.key().as_ref(), vault.key().as_ref()],
+        bump
+    )]
+    /// CHECK: PDA verified via seeds
+    pub vault_authority: UncheckedAccount<'info>,
+
+    pub cp_swap_program: Program<'info, RaydiumCpSwap>,
+    /// CHECK: Validated by Raydium
+    pub amm_config: UncheckedAccount<'info>,
+    #[account(
+        seeds = [
+            raydium_cp_swap::AUTH_SEED.as_bytes(),
+        ],
+        seeds::program = cp_swap_program,
+        bump,
+    )]
+    /// CHECK: pool vault and lp mint authority
+    pub authority: UncheckedAccount<'info>,
+    /// CHECK: Initialized by Raydium
+    #[account(mut)]
+    pub pool_state: UncheckedAccount<'info>,
+    pub token_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Initialized by Raydium
+    #[account(mut)]
+    pub lp_mint: UncheckedAccount<'info>,
+    /// CHECK: Validated by Raydium
+    #[account(mut)]
+    pub creator_token_0: UncheckedAccount<'info>,
+    /// CHECK: Validated by Raydium
+    #[account(mut)]
+    pub creator_token_1: UncheckedAccount<'info>,
+    /// CHECK: Creator's LP account, created by Raydium
+    #[account(mut)]
+    pub creator_lp_token: UncheckedAccount<'info>,
+    /// CHECK: Initialized by Raydium
+    #[account(mut)]
+    pub token_0_vault: UncheckedAccount<'info>,
+    /// CHECK: Initialized by Raydium
+    #[account(mut)]
+    pub token_1_vault: UncheckedAccount<'info>,
+    /// CHECK: Validated by Raydium
+    #[account(mut)]
+    pub create_pool_fee: UncheckedAccount<'info>,
+    /// CHECK: Initialized by Raydium
+    #[account(mut)]
+    pub observation_state: UncheckedAccount<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_0_program: Interface<'info, TokenInterface>,
+    pub token_1_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
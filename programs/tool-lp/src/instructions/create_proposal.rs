@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{
+    Error, Proposal, ProposalCreatedEvent, ProposalKind, UserLock, Vault, MAX_FEE_BPS,
+    PROPOSAL_SEED, PROPOSAL_VOTING_PERIOD, USER_LOCK_SEED,
+};
+
+pub fn handler(ctx: Context<CreateProposal>, kind: ProposalKind) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let proposal = &mut ctx.accounts.proposal;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    ctx.accounts.user_lock.assert_owner(
+        &ctx.accounts.proposer.key(),
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+    require!(
+        ctx.accounts.user_lock.amount > 0,
+        Error::InsufficientBalance
+    );
+    match kind {
+        ProposalKind::FeeBps { fee_bps } => require!(fee_bps <= MAX_FEE_BPS, Error::FeeTooHigh),
+        ProposalKind::FeeRecipient { fee_recipient } => {
+            require!(fee_recipient != Pubkey::default(), Error::InvalidInput)
+        }
+    }
+
+    proposal.vault = vault.key();
+    proposal.proposal_id = vault.proposal_count;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.kind = kind;
+    proposal.snapshot_timestamp = current_timestamp;
+    proposal.snapshot_total_locked = vault.total_locked;
+    proposal.voting_ends_at = current_timestamp
+        .checked_add(PROPOSAL_VOTING_PERIOD)
+        .ok_or(Error::ArithmeticError)?;
+    proposal.votes_for = 0;
+    proposal.votes_against = 0;
+    proposal.executed = false;
+    proposal.bump = ctx.bumps.proposal;
+
+    vault.proposal_count = vault
+        .proposal_count
+        .checked_add(1)
+        .ok_or(Error::ArithmeticError)?;
+
+    emit!(ProposalCreatedEvent {
+        vault: vault.key(),
+        proposal: proposal.key(),
+        proposer: proposal.proposer,
+        kind,
+        voting_ends_at: proposal.voting_ends_at,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::LEN,
+        seeds = [
+            PROPOSAL_SEED,
+            vault.key().as_ref(),
+            vault.proposal_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    pub system_program: Program<'info, System>,
+}
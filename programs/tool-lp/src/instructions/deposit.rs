@@ -2,14 +2,32 @@ use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
 use raydium_cp_swap::states::PoolState;
 
-use crate::{DepositEvent, Error, UserLock, Vault};
+use crate::{math, DepositEvent, Error, UserLock, Vault, USER_LOCK_SEED};
 
 pub fn handler(ctx: Context<Deposit>, amount: u64, unlock_timestamp: i64) -> Result<()> {
+    // A tokenized lock belongs to the NFT holder, not to the address it is
+    // derived from, so only the holder may add to it.
+    if ctx.accounts.user_lock.is_tokenized() {
+        ctx.accounts.user_lock.assert_owner(
+            &ctx.accounts.user.key(),
+            ctx.accounts.position_token_account.as_deref(),
+        )?;
+    }
+
     let vault = &mut ctx.accounts.vault;
     let user_lock = &mut ctx.accounts.user_lock;
     let current_timestamp = Clock::get()?.unix_timestamp;
 
-    require!(unlock_timestamp > current_timestamp, Error::InvalidInput);
+    require!(
+        amount > 0 && amount >= vault.lock_policy.min_deposit,
+        Error::DepositTooSmall
+    );
+    user_lock.check_deposit_terms(
+        vault,
+        ctx.accounts.user.key() == ctx.accounts.beneficiary.key(),
+        unlock_timestamp,
+        current_timestamp,
+    )?;
 
     require!(
         ctx.accounts.user_token_account.mint == vault.token_mint,
@@ -23,12 +41,10 @@ pub fn handler(ctx: Context<Deposit>, amount: u64, unlock_timestamp: i64) -> Res
         ctx.accounts.token_0_vault.amount,
         ctx.accounts.token_1_vault.amount,
     );
-    let deposit_token_per_lp_0 = vault_0_amount
-        .checked_div(pool_state.lp_supply)
-        .ok_or(Error::ArithmeticError)?;
-    let deposit_token_per_lp_1 = vault_1_amount
-        .checked_div(pool_state.lp_supply)
-        .ok_or(Error::ArithmeticError)?;
+    let deposit_basis_0 =
+        math::token_per_lp(vault_0_amount, pool_state.lp_supply).map_err(Error::from)?;
+    let deposit_basis_1 =
+        math::token_per_lp(vault_1_amount, pool_state.lp_supply).map_err(Error::from)?;
 
     token_interface::transfer_checked(
         CpiContext::new(
@@ -44,22 +60,20 @@ pub fn handler(ctx: Context<Deposit>, amount: u64, unlock_timestamp: i64) -> Res
         ctx.accounts.token_mint.decimals,
     )?;
 
-    user_lock.user = ctx.accounts.user.key();
-    user_lock.amount = user_lock
-        .amount
-        .checked_add(amount)
-        .ok_or(Error::ArithmeticError)?;
-    user_lock.unlock_timestamp = unlock_timestamp;
-    user_lock.deposit_token_per_lp_0 = deposit_token_per_lp_0;
-    user_lock.deposit_token_per_lp_1 = deposit_token_per_lp_1;
-
-    vault.total_locked = vault
-        .total_locked
-        .checked_add(amount)
-        .ok_or(Error::ArithmeticError)?;
+    user_lock.user = ctx.accounts.beneficiary.key();
+    user_lock.vault = vault.key();
+    user_lock.credit(
+        vault,
+        amount,
+        unlock_timestamp,
+        deposit_basis_0,
+        deposit_basis_1,
+        current_timestamp,
+    )?;
 
     emit!(DepositEvent {
-        user: ctx.accounts.user.key(),
+        user: ctx.accounts.beneficiary.key(),
+        depositor: ctx.accounts.user.key(),
         vault: vault.key(),
         pool_state: vault.pool_state,
         amount,
@@ -74,20 +88,23 @@ pub fn handler(ctx: Context<Deposit>, amount: u64, unlock_timestamp: i64) -> Res
 #[instruction(amount: u64, unlock_timestamp: i64)]
 pub struct Deposit<'info> {
     #[account(mut)]
-    pub vault: Account<'info, Vault>,
+    pub vault: Box<Account<'info, Vault>>,
+    /// Pays for account creation unless `payer` is given
     #[account(mut)]
     pub user: Signer<'info>,
+    /// CHECK: Owner of the lock; may differ from the funding `user`
+    pub beneficiary: UncheckedAccount<'info>,
     #[account(
         init_if_needed,
-        payer = user,
+        payer = payer.as_ref().unwrap_or(&user),
         space = 8 + UserLock::LEN,
-        seeds = [b"user-lock", vault.key().as_ref(), user.key().as_ref()],
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), beneficiary.key().as_ref()],
         bump
     )]
-    pub user_lock: Account<'info, UserLock>,
+    pub user_lock: Box<Account<'info, UserLock>>,
     #[account(mut, token::mint = token_mint)]
     pub user_token_account: InterfaceAccount<'info, TokenAccount>,
-    #[account(mut, token::mint = token_mint)]
+    #[account(mut, address = vault.vault_token_account)]
     pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
     pub token_mint: InterfaceAccount<'info, Mint>,
     #[account(address = vault.pool_state)]
@@ -105,4 +122,9 @@ pub struct Deposit<'info> {
     pub token_program: Interface<'info, TokenInterface>,
     pub clock: Sysvar<'info, Clock>,
     pub system_program: Program<'info, System>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Pays for account creation instead of `user`; kept last so it can be omitted
+    #[account(mut)]
+    pub payer: Option<Signer<'info>>,
 }
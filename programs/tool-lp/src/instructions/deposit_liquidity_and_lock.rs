@@ -0,0 +1,304 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    self, Mint, Token2022, TokenAccount, TokenInterface, TransferChecked,
+};
+use raydium_cp_swap::{cpi, program::RaydiumCpSwap, states::PoolState};
+
+use crate::{
+    math,
+    utils::{amount_after_transfer_fee, amount_before_transfer_fee},
+    DepositEvent, Error, UserLock, Vault, USER_LOCK_SEED, VAULT_AUTHORITY_SEED,
+};
+
+pub fn handler(
+    ctx: Context<DepositLiquidityAndLock>,
+    maximum_token_0_amount: u64,
+    maximum_token_1_amount: u64,
+    minimum_lp_amount: u64,
+    unlock_timestamp: i64,
+) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(
+        maximum_token_0_amount > 0 && maximum_token_1_amount > 0,
+        Error::InvalidInput
+    );
+    // Once the NFT is sold, the user's lock address belongs to the holder.
+    if ctx.accounts.user_lock.is_tokenized() {
+        ctx.accounts.user_lock.assert_owner(
+            &ctx.accounts.user.key(),
+            ctx.accounts.position_token_account.as_deref(),
+        )?;
+    }
+    ctx.accounts.user_lock.check_deposit_terms(
+        &ctx.accounts.vault,
+        true,
+        unlock_timestamp,
+        current_timestamp,
+    )?;
+
+    {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        require!(
+            ctx.accounts.token_0_vault.key() == pool_state.token_0_vault,
+            Error::InvalidInput
+        );
+        require!(
+            ctx.accounts.token_1_vault.key() == pool_state.token_1_vault,
+            Error::InvalidInput
+        );
+        require!(
+            ctx.accounts.lp_mint.key() == pool_state.lp_mint,
+            Error::InvalidInput
+        );
+        require!(
+            ctx.accounts.token_0_program.key() == pool_state.token_0_program,
+            Error::InvalidInput
+        );
+        require!(
+            ctx.accounts.token_1_program.key() == pool_state.token_1_program,
+            Error::InvalidInput
+        );
+    }
+
+    // Raydium takes the pool tokens from the LP owner, so they pass through
+    // the vault authority's accounts and any transfer fee is paid twice: on
+    // the way in and on the way into the pool. The LP and the exact amounts
+    // are worked out from the maximums first, so only those are moved.
+    let (lp_token_amount, token_0_amount, token_1_amount) = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        let (vault_0_amount, vault_1_amount) = pool_state.vault_amount_without_fee(
+            ctx.accounts.token_0_vault.amount,
+            ctx.accounts.token_1_vault.amount,
+        );
+        let vault_0_mint = &ctx.accounts.vault_0_mint;
+        let vault_1_mint = &ctx.accounts.vault_1_mint;
+        let available_token_0_amount = amount_after_transfer_fee(
+            vault_0_mint,
+            amount_after_transfer_fee(vault_0_mint, maximum_token_0_amount)?,
+        )?;
+        let available_token_1_amount = amount_after_transfer_fee(
+            vault_1_mint,
+            amount_after_transfer_fee(vault_1_mint, maximum_token_1_amount)?,
+        )?;
+
+        let lp_for_token_0 = math::tokens_to_lp(
+            available_token_0_amount,
+            vault_0_amount,
+            pool_state.lp_supply,
+        )
+        .map_err(Error::from)?;
+        let lp_for_token_1 = math::tokens_to_lp(
+            available_token_1_amount,
+            vault_1_amount,
+            pool_state.lp_supply,
+        )
+        .map_err(Error::from)?;
+        let lp_token_amount = lp_for_token_0.min(lp_for_token_1);
+        require!(
+            lp_token_amount > 0 && lp_token_amount >= minimum_lp_amount,
+            Error::SlippageExceeded
+        );
+
+        // What Raydium moves from the vault authority's accounts for that LP.
+        let token_0_amount = amount_before_transfer_fee(
+            vault_0_mint,
+            math::lp_to_tokens_ceil(lp_token_amount, vault_0_amount, pool_state.lp_supply)
+                .map_err(Error::from)?,
+        )?;
+        let token_1_amount = amount_before_transfer_fee(
+            vault_1_mint,
+            math::lp_to_tokens_ceil(lp_token_amount, vault_1_amount, pool_state.lp_supply)
+                .map_err(Error::from)?,
+        )?;
+        (lp_token_amount, token_0_amount, token_1_amount)
+    };
+    let user_token_0_amount =
+        amount_before_transfer_fee(&ctx.accounts.vault_0_mint, token_0_amount)?;
+    let user_token_1_amount =
+        amount_before_transfer_fee(&ctx.accounts.vault_1_mint, token_1_amount)?;
+    require!(
+        user_token_0_amount <= maximum_token_0_amount
+            && user_token_1_amount <= maximum_token_1_amount,
+        Error::SlippageExceeded
+    );
+    let vault_lp_balance = ctx.accounts.vault_token_account.amount;
+
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_0_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_token_0_account.to_account_info(),
+                to: ctx.accounts.vault_token_0_account.to_account_info(),
+                mint: ctx.accounts.vault_0_mint.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        user_token_0_amount,
+        ctx.accounts.vault_0_mint.decimals,
+    )?;
+    token_interface::transfer_checked(
+        CpiContext::new(
+            ctx.accounts.token_1_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_token_1_account.to_account_info(),
+                to: ctx.accounts.vault_token_1_account.to_account_info(),
+                mint: ctx.accounts.vault_1_mint.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        user_token_1_amount,
+        ctx.accounts.vault_1_mint.decimals,
+    )?;
+
+    let vault_key = ctx.accounts.vault.key();
+    let pool_state_key = ctx.accounts.pool_state.key();
+    let seeds = &[
+        VAULT_AUTHORITY_SEED,
+        pool_state_key.as_ref(),
+        vault_key.as_ref(),
+        &[ctx.accounts.vault.authority_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    cpi::deposit(
+        CpiContext::new_with_signer(
+            ctx.accounts.cp_swap_program.to_account_info(),
+            cpi::accounts::Deposit {
+                owner: ctx.accounts.vault_authority.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+                pool_state: ctx.accounts.pool_state.to_account_info(),
+                owner_lp_token: ctx.accounts.vault_token_account.to_account_info(),
+                token_0_account: ctx.accounts.vault_token_0_account.to_account_info(),
+                token_1_account: ctx.accounts.vault_token_1_account.to_account_info(),
+                token_0_vault: ctx.accounts.token_0_vault.to_account_info(),
+                token_1_vault: ctx.accounts.token_1_vault.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                token_program_2022: ctx.accounts.token_program_2022.to_account_info(),
+                vault_0_mint: ctx.accounts.vault_0_mint.to_account_info(),
+                vault_1_mint: ctx.accounts.vault_1_mint.to_account_info(),
+                lp_mint: ctx.accounts.lp_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        lp_token_amount,
+        token_0_amount,
+        token_1_amount,
+    )?;
+
+    ctx.accounts.vault_token_account.reload()?;
+    ctx.accounts.token_0_vault.reload()?;
+    ctx.accounts.token_1_vault.reload()?;
+
+    let amount = ctx
+        .accounts
+        .vault_token_account
+        .amount
+        .checked_sub(vault_lp_balance)
+        .ok_or(Error::ArithmeticError)?;
+    require!(
+        amount >= ctx.accounts.vault.lock_policy.min_deposit,
+        Error::DepositTooSmall
+    );
+
+    // Basis at the price the LP was just minted at.
+    let (deposit_basis_0, deposit_basis_1) = {
+        let pool_state = ctx.accounts.pool_state.load()?;
+        let (vault_0_amount, vault_1_amount) = pool_state.vault_amount_without_fee(
+            ctx.accounts.token_0_vault.amount,
+            ctx.accounts.token_1_vault.amount,
+        );
+        (
+            math::token_per_lp(vault_0_amount, pool_state.lp_supply).map_err(Error::from)?,
+            math::token_per_lp(vault_1_amount, pool_state.lp_supply).map_err(Error::from)?,
+        )
+    };
+
+    let vault = &mut ctx.accounts.vault;
+    let user_lock = &mut ctx.accounts.user_lock;
+    user_lock.user = ctx.accounts.user.key();
+    user_lock.vault = vault.key();
+    user_lock.credit(
+        vault,
+        amount,
+        unlock_timestamp,
+        deposit_basis_0,
+        deposit_basis_1,
+        current_timestamp,
+    )?;
+
+    emit!(DepositEvent {
+        user: ctx.accounts.user.key(),
+        depositor: ctx.accounts.user.key(),
+        vault: vault.key(),
+        pool_state: vault.pool_state,
+        amount,
+        unlock_timestamp,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositLiquidityAndLock<'info> {
+    #[account(mut, has_one = pool_state)]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserLock::LEN,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    #[account(mut, address = vault.vault_token_account)]
+    pub vault_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = vault_0_mint, token::authority = vault_authority)]
+    pub vault_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = vault_1_mint, token::authority = vault_authority)]
+    pub vault_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = vault_0_mint, token::authority = user)]
+    pub user_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut, token::mint = vault_1_mint, token::authority = user)]
+    pub user_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(mut)]
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, vault.pool_state.as_ref(), vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    /// CHECK: PDA verified via seeds
+    pub vault_authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    pub cp_swap_program: Program<'info, RaydiumCpSwap>,
+    #[account(
+        seeds = [
+            raydium_cp_swap::AUTH_SEED.as_bytes(),
+        ],
+        seeds::program = cp_swap_program,
+        bump,
+    )]
+    /// CHECK: pool vault and lp mint authority
+    pub authority: UncheckedAccount<'info>,
+
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, address = vault.token_mint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_0_program: Interface<'info, TokenInterface>,
+    pub token_1_program: Interface<'info, TokenInterface>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_2022: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
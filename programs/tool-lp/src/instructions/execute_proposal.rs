@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    Error, Proposal, ProposalExecutedEvent, ProposalKind, Vault, BPS_DENOMINATOR,
+    PROPOSAL_QUORUM_BPS, PROPOSAL_SEED,
+};
+
+pub fn handler(ctx: Context<ExecuteProposal>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let proposal = &mut ctx.accounts.proposal;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(!proposal.executed, Error::ProposalAlreadyExecuted);
+    require!(
+        current_timestamp >= proposal.voting_ends_at,
+        Error::VotingNotEnded
+    );
+
+    let total_votes = (proposal.votes_for as u128)
+        .checked_add(proposal.votes_against as u128)
+        .ok_or(Error::ArithmeticError)?;
+    let quorum = (proposal.snapshot_total_locked as u128)
+        .checked_mul(PROPOSAL_QUORUM_BPS as u128)
+        .ok_or(Error::ArithmeticError)?
+        / BPS_DENOMINATOR as u128;
+    require!(
+        total_votes > 0 && total_votes >= quorum && proposal.votes_for > proposal.votes_against,
+        Error::ProposalNotPassed
+    );
+
+    match proposal.kind {
+        ProposalKind::FeeBps { fee_bps } => vault.fee_bps = fee_bps,
+        ProposalKind::FeeRecipient { fee_recipient } => vault.fee_recipient = fee_recipient,
+    }
+    proposal.executed = true;
+
+    emit!(ProposalExecutedEvent {
+        vault: vault.key(),
+        proposal: proposal.key(),
+        kind: proposal.kind,
+        votes_for: proposal.votes_for,
+        votes_against: proposal.votes_against,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut, address = proposal.vault)]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        mut,
+        seeds = [
+            PROPOSAL_SEED,
+            proposal.vault.as_ref(),
+            proposal.proposal_id.to_le_bytes().as_ref()
+        ],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
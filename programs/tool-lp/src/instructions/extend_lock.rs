@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{Error, LockExtendedEvent, UserLock, Vault, USER_LOCK_SEED};
+
+pub fn handler(ctx: Context<ExtendLock>, unlock_timestamp: i64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let user_lock = &mut ctx.accounts.user_lock;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    user_lock.assert_owner(
+        &ctx.accounts.user.key(),
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+    require!(user_lock.amount > 0, Error::InsufficientBalance);
+    require!(
+        unlock_timestamp > user_lock.unlock_timestamp && unlock_timestamp > current_timestamp,
+        Error::InvalidInput
+    );
+    vault
+        .lock_policy
+        .validate_duration(unlock_timestamp - current_timestamp)?;
+
+    vault.update_rewards(current_timestamp)?;
+    user_lock.settle_rewards(vault, current_timestamp)?;
+
+    let previous_unlock_timestamp = user_lock.unlock_timestamp;
+    user_lock.unlock_timestamp = unlock_timestamp;
+    user_lock.refresh_weight(vault, current_timestamp)?;
+    user_lock.reset_reward_debts(vault)?;
+
+    emit!(LockExtendedEvent {
+        user: ctx.accounts.user.key(),
+        vault: vault.key(),
+        previous_unlock_timestamp,
+        unlock_timestamp,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ExtendLock<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
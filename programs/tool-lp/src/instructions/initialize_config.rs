@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+
+use crate::{Config, ADMIN_WALLET, CONFIG_SEED};
+
+pub fn handler(ctx: Context<InitializeConfig>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.admin = ctx.accounts.admin.key();
+    config.permissionless_vaults = true;
+    config.bump = ctx.bumps.config;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Config::LEN,
+        seeds = [CONFIG_SEED],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut, address = ADMIN_WALLET)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
@@ -5,12 +5,22 @@ use anchor_spl::{
 };
 use raydium_cp_swap::states::PoolState;
 
-use crate::{Error, Vault};
+use crate::{
+    Config, Error, PoolAllowlistEntry, Vault, CONFIG_SEED, POOL_ALLOWLIST_SEED,
+    VAULT_AUTHORITY_SEED, VAULT_SEED, VAULT_TOKEN_SEED,
+};
 
 pub fn handler(ctx: Context<InitializeVault>) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let pool_state = ctx.accounts.pool_state.load()?;
 
+    if !Config::allows_permissionless_vaults(&ctx.accounts.config)? {
+        require!(
+            ctx.accounts.pool_allowlist.is_some(),
+            Error::PoolNotAllowlisted
+        );
+    }
+
     require!(
         pool_state.lp_mint == ctx.accounts.token_mint.key(),
         Error::InvalidInput
@@ -25,11 +35,14 @@ pub fn handler(ctx: Context<InitializeVault>) -> Result<()> {
         Error::InvalidInput
     );
 
-    vault.pool_state = ctx.accounts.pool_state.key();
-    vault.token_mint = ctx.accounts.token_mint.key();
-    vault.vault_token_account = ctx.accounts.vault_token_account.key();
-    vault.total_locked = 0;
-    vault.bump = ctx.bumps.vault;
+    vault.initialize(
+        ctx.accounts.pool_state.key(),
+        ctx.accounts.token_mint.key(),
+        ctx.accounts.vault_token_account.key(),
+        ctx.accounts.initializer.key(),
+        ctx.bumps.vault,
+        ctx.bumps.vault_authority,
+    );
 
     if ctx
         .accounts
@@ -78,10 +91,10 @@ pub struct InitializeVault<'info> {
         init,
         payer = initializer,
         space = 8 + Vault::LEN,
-        seeds = [b"vault", pool_state.key().as_ref()],
+        seeds = [VAULT_SEED, pool_state.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, Vault>,
+    pub vault: Box<Account<'info, Vault>>,
     #[account(mut)]
     pub initializer: Signer<'info>,
     #[account(address = pool_state.load()?.token_0_vault)]
@@ -99,7 +112,7 @@ pub struct InitializeVault<'info> {
         payer = initializer,
         token::mint = token_mint,
         token::authority = vault_authority,
-        seeds = [b"vault-token", pool_state.key().as_ref(), vault.key().as_ref()],
+        seeds = [VAULT_TOKEN_SEED, pool_state.key().as_ref(), vault.key().as_ref()],
         bump
     )]
     pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
@@ -110,7 +123,7 @@ pub struct InitializeVault<'info> {
     #[account(mut)]
     pub vault_token_1_account: UncheckedAccount<'info>,
     #[account(
-        seeds = [b"vault-authority", pool_state.key().as_ref(), vault.key().as_ref()],
+        seeds = [VAULT_AUTHORITY_SEED, pool_state.key().as_ref(), vault.key().as_ref()],
         bump
     )]
     /// CHECK: PDA verified via seeds
@@ -120,4 +133,13 @@ pub struct InitializeVault<'info> {
     pub token_0_program: Interface<'info, TokenInterface>,
     pub token_1_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
+    /// CHECK: Program config; may not be initialized yet, see `Config::allows_permissionless_vaults`
+    #[account(seeds = [CONFIG_SEED], bump)]
+    pub config: UncheckedAccount<'info>,
+    /// Required unless the config allows permissionless vaults
+    #[account(
+        seeds = [POOL_ALLOWLIST_SEED, pool_state.key().as_ref()],
+        bump = pool_allowlist.bump
+    )]
+    pub pool_allowlist: Option<Account<'info, PoolAllowlistEntry>>,
 }
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{
+    Error, LockMadePermanentEvent, UserLock, Vault, PERMANENT_UNLOCK_TIMESTAMP, USER_LOCK_SEED,
+};
+
+pub fn handler(ctx: Context<MakeLockPermanent>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let user_lock = &mut ctx.accounts.user_lock;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    user_lock.assert_owner(
+        &ctx.accounts.user.key(),
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+    require!(!user_lock.is_permanent, Error::PermanentLock);
+    require!(user_lock.amount > 0, Error::InsufficientBalance);
+
+    vault.update_rewards(current_timestamp)?;
+    user_lock.settle_rewards(vault, current_timestamp)?;
+
+    // There is no way back: the lock never reaches its unlock time and keeps full weight.
+    user_lock.is_permanent = true;
+    user_lock.unlock_timestamp = PERMANENT_UNLOCK_TIMESTAMP;
+    user_lock.rollover_duration = 0;
+    vault.total_permanent_locked = vault
+        .total_permanent_locked
+        .checked_add(user_lock.amount)
+        .ok_or(Error::ArithmeticError)?;
+    user_lock.refresh_weight(vault, current_timestamp)?;
+    user_lock.reset_reward_debts(vault)?;
+
+    emit!(LockMadePermanentEvent {
+        user: ctx.accounts.user.key(),
+        vault: vault.key(),
+        user_lock: user_lock.key(),
+        amount: user_lock.amount,
+        total_permanent_locked: vault.total_permanent_locked,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MakeLockPermanent<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
@@ -0,0 +1,191 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::{self, Burn, CloseAccount},
+    token_interface::{Mint, Token2022, TokenAccount},
+};
+
+use crate::{math, Error, LocksMergedEvent, UserLock, Vault, USER_LOCK_SEED, VAULT_AUTHORITY_SEED};
+
+pub fn handler(ctx: Context<MergeLocks>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let source = &mut ctx.accounts.source_lock;
+    let destination = &mut ctx.accounts.destination_lock;
+    let owner = ctx.accounts.owner.key();
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require_keys_neq!(source.key(), destination.key(), Error::InvalidInput);
+    source.assert_owner(
+        &owner,
+        ctx.accounts.source_position_token_account.as_deref(),
+    )?;
+    destination.assert_owner(
+        &owner,
+        ctx.accounts.destination_position_token_account.as_deref(),
+    )?;
+    require!(source.amount > 0, Error::InsufficientBalance);
+    // Folding a permanent lock into a time lock would make its principal withdrawable.
+    require!(
+        !source.is_permanent || destination.is_permanent,
+        Error::PermanentLock
+    );
+
+    vault.update_rewards(current_timestamp)?;
+    source.settle_rewards(vault, current_timestamp)?;
+    destination.settle_rewards(vault, current_timestamp)?;
+
+    // Renew expired rollover terms first, so neither lock merges as unlocked
+    // while it is still rolling. The merged lock keeps one rollover setting.
+    source.roll_over(current_timestamp)?;
+    destination.roll_over(current_timestamp)?;
+    require!(
+        source.rollover_duration == destination.rollover_duration,
+        Error::RolloverMismatch
+    );
+
+    let merged_amount = destination
+        .amount
+        .checked_add(source.amount)
+        .ok_or(Error::ArithmeticError)?;
+    let deposit_basis_0 = math::blend_basis(
+        destination.amount,
+        destination.deposit_basis_0,
+        source.amount,
+        source.deposit_basis_0,
+    )
+    .map_err(Error::from)?;
+    let deposit_basis_1 = math::blend_basis(
+        destination.amount,
+        destination.deposit_basis_1,
+        source.amount,
+        source.deposit_basis_1,
+    )
+    .map_err(Error::from)?;
+    destination.set_basis(deposit_basis_0, deposit_basis_1);
+    if destination.is_permanent && !source.is_permanent {
+        vault.total_permanent_locked = vault
+            .total_permanent_locked
+            .checked_add(source.amount)
+            .ok_or(Error::ArithmeticError)?;
+    }
+    destination.amount = merged_amount;
+    destination.unlock_timestamp = destination.unlock_timestamp.max(source.unlock_timestamp);
+    destination.last_deposit_timestamp = current_timestamp;
+    destination.checkpoint_votes(current_timestamp);
+    for (owed, source_owed) in destination
+        .rewards_owed
+        .iter_mut()
+        .zip(source.rewards_owed.iter())
+    {
+        *owed = owed
+            .checked_add(*source_owed)
+            .ok_or(Error::ArithmeticError)?;
+    }
+
+    let merged_lp_amount = source.amount;
+    source.amount = 0;
+    source.refresh_weight(vault, current_timestamp)?;
+    destination.refresh_weight(vault, current_timestamp)?;
+    destination.reset_reward_debts(vault)?;
+
+    // The source position NFT no longer represents anything.
+    if source.is_tokenized() {
+        let position_mint = ctx
+            .accounts
+            .source_position_mint
+            .as_ref()
+            .ok_or(Error::InvalidInput)?;
+        let position_token_account = ctx
+            .accounts
+            .source_position_token_account
+            .as_ref()
+            .ok_or(Error::InvalidInput)?;
+        let token_2022_program = ctx
+            .accounts
+            .token_2022_program
+            .as_ref()
+            .ok_or(Error::InvalidInput)?;
+        require_keys_eq!(
+            position_mint.key(),
+            source.position_mint,
+            Error::InvalidInput
+        );
+
+        token_2022::burn(
+            CpiContext::new(
+                token_2022_program.to_account_info(),
+                Burn {
+                    mint: position_mint.to_account_info(),
+                    from: position_token_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        let vault_key = vault.key();
+        let seeds = &[
+            VAULT_AUTHORITY_SEED,
+            vault.pool_state.as_ref(),
+            vault_key.as_ref(),
+            &[vault.authority_bump],
+        ];
+        token_2022::close_account(CpiContext::new_with_signer(
+            token_2022_program.to_account_info(),
+            CloseAccount {
+                account: position_mint.to_account_info(),
+                destination: ctx.accounts.owner.to_account_info(),
+                authority: ctx.accounts.vault_authority.to_account_info(),
+            },
+            &[&seeds[..]],
+        ))?;
+    }
+
+    emit!(LocksMergedEvent {
+        vault: vault.key(),
+        owner,
+        source_lock: source.key(),
+        destination_lock: destination.key(),
+        amount: merged_lp_amount,
+        total_amount: destination.amount,
+        unlock_timestamp: destination.unlock_timestamp,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MergeLocks<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), source_lock.user.as_ref()],
+        bump
+    )]
+    pub source_lock: Box<Account<'info, UserLock>>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), destination_lock.user.as_ref()],
+        bump
+    )]
+    pub destination_lock: Box<Account<'info, UserLock>>,
+    /// Required when the source lock is tokenized
+    #[account(mut)]
+    pub source_position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Required when the source lock is tokenized
+    #[account(mut)]
+    pub source_position_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Required when the destination lock is tokenized
+    pub destination_position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, vault.pool_state.as_ref(), vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    /// CHECK: PDA verified via seeds
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_2022_program: Option<Program<'info, Token2022>>,
+}
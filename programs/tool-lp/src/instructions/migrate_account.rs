@@ -0,0 +1,126 @@
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+use crate::{
+    Error, UserLock, Vault, VoteCheckpoint, ADMIN_WALLET, DEFAULT_FEE_BPS, TOKEN_PER_LP_SCALE,
+    USER_LOCK_SEED, VAULT_AUTHORITY_SEED,
+};
+
+/// Grows a `Vault` or `UserLock` created under an older, shorter layout to the
+/// current one. Fields are only ever appended, so the existing data stays in
+/// place and the new fields start zeroed. Fields whose zero value is not
+/// usable get the defaults a new vault or lock would have. A `UserLock` also
+/// needs its vault passed, which is checked against the lock's address.
+/// Permissionless, and a no-op for accounts that are already current.
+pub fn handler(ctx: Context<MigrateAccount>) -> Result<()> {
+    let account = ctx.accounts.account.to_account_info();
+    let (space, is_vault) = {
+        let data = account.try_borrow_data()?;
+        let discriminator = data
+            .get(..8)
+            .ok_or(ErrorCode::AccountDiscriminatorNotFound)?;
+        if discriminator == Vault::DISCRIMINATOR {
+            (8 + Vault::LEN, true)
+        } else if discriminator == UserLock::DISCRIMINATOR {
+            (8 + UserLock::LEN, false)
+        } else {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+    };
+    if account.data_len() >= space {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(space);
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    account.realloc(space, true)?;
+
+    if is_vault {
+        let mut vault = Vault::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        if vault.authority_bump == 0 {
+            let (_, authority_bump) = Pubkey::find_program_address(
+                &[
+                    VAULT_AUTHORITY_SEED,
+                    vault.pool_state.as_ref(),
+                    account.key.as_ref(),
+                ],
+                ctx.program_id,
+            );
+            vault.authority_bump = authority_bump;
+        }
+        if vault.fee_recipient == Pubkey::default() {
+            vault.fee_bps = DEFAULT_FEE_BPS;
+            vault.fee_recipient = ADMIN_WALLET;
+        }
+        if vault.weight_duration == 0 {
+            vault.weight_duration = vault.max_lock_duration();
+        }
+        vault.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    } else {
+        let mut user_lock = UserLock::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        if user_lock.vault == Pubkey::default() {
+            let vault = ctx
+                .accounts
+                .vault
+                .as_ref()
+                .ok_or(Error::InvalidInput)?
+                .key();
+            let (expected, _) = Pubkey::find_program_address(
+                &[USER_LOCK_SEED, vault.as_ref(), user_lock.user.as_ref()],
+                ctx.program_id,
+            );
+            require_keys_eq!(expected, account.key(), Error::InvalidInput);
+            user_lock.vault = vault;
+        }
+        if user_lock.vote_checkpoints[1].timestamp == 0 {
+            // Nothing was added since the last deposit, so the lock has held at
+            // least its current amount since then.
+            user_lock.vote_checkpoints[1] = VoteCheckpoint {
+                amount: user_lock.amount,
+                timestamp: user_lock.last_deposit_timestamp,
+            };
+        }
+        if user_lock.keeper_enabled && user_lock.keeper_authority == Pubkey::default() {
+            // Keepers enabled before the authority was recorded stay enabled
+            // for the depositor only; a later NFT holder has to re-enable them.
+            user_lock.keeper_authority = user_lock.user;
+        }
+        if user_lock.deposit_basis_0 == 0 && user_lock.deposit_basis_1 == 0 {
+            // Older locks only recorded the whole-token basis, rounded down, and
+            // nothing else to recompute it from. It is kept as is, so a migrated
+            // lock may pay the fee on up to one token per LP of its principal.
+            user_lock.set_basis(
+                user_lock.deposit_token_per_lp_0 as u128 * TOKEN_PER_LP_SCALE,
+                user_lock.deposit_token_per_lp_1 as u128 * TOKEN_PER_LP_SCALE,
+            );
+        }
+        user_lock.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: a `Vault` or `UserLock` of this program, checked by discriminator
+    #[account(mut, owner = crate::ID)]
+    pub account: UncheckedAccount<'info>,
+    /// Tops up the rent for the extra space
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the vault of a `UserLock` being migrated, checked against the
+    /// lock's address; unused for a `Vault`
+    pub vault: Option<UncheckedAccount<'info>>,
+}
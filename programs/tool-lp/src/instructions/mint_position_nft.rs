@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, Token2022, TokenAccount},
+};
+
+use crate::{
+    utils::PositionNft, Error, UserLock, Vault, POSITION_MINT_SEED, USER_LOCK_SEED,
+    VAULT_AUTHORITY_SEED,
+};
+
+/// Tokenizes a lock, which then changes hands with its position NFT. Locks are
+/// created without one.
+pub fn handler(ctx: Context<MintPositionNft>, uri: String) -> Result<()> {
+    require!(
+        ctx.accounts.user_lock.amount > 0,
+        Error::InsufficientBalance
+    );
+
+    let owner = ctx.accounts.owner.to_account_info();
+    PositionNft {
+        mint: ctx.accounts.position_mint.to_account_info(),
+        token_account: ctx.accounts.position_token_account.to_account_info(),
+        vault_authority: ctx.accounts.vault_authority.to_account_info(),
+        token_2022_program: ctx.accounts.token_2022_program.to_account_info(),
+        associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    }
+    .issue(
+        &ctx.accounts.vault,
+        &mut ctx.accounts.user_lock,
+        &owner,
+        &ctx.accounts.payer.to_account_info(),
+        uri,
+    )
+}
+
+#[derive(Accounts)]
+pub struct MintPositionNft<'info> {
+    pub vault: Box<Account<'info, Vault>>,
+    pub owner: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    #[account(
+        init,
+        payer = payer,
+        mint::decimals = 0,
+        mint::authority = vault_authority,
+        mint::token_program = token_2022_program,
+        extensions::metadata_pointer::authority = vault_authority,
+        extensions::metadata_pointer::metadata_address = position_mint,
+        extensions::close_authority::authority = vault_authority,
+        extensions::permanent_delegate::delegate = vault_authority,
+        seeds = [POSITION_MINT_SEED, user_lock.key().as_ref()],
+        bump
+    )]
+    pub position_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = position_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_2022_program
+    )]
+    pub position_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        seeds = [VAULT_AUTHORITY_SEED, vault.pool_state.as_ref(), vault.key().as_ref()],
+        bump = vault.authority_bump
+    )]
+    /// CHECK: PDA verified via seeds
+    pub vault_authority: UncheckedAccount<'info>,
+    pub token_2022_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
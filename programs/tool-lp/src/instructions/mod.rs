@@ -1,9 +1,61 @@
+pub mod add_pool_to_allowlist;
+pub mod add_reward;
+pub mod batch_withdraw;
+pub mod cast_vote;
+pub mod claim_lp_fees;
+pub mod claim_rewards;
+pub mod create_pool_and_lock;
+pub mod create_proposal;
 pub mod deposit;
+pub mod deposit_liquidity_and_lock;
+pub mod execute_proposal;
+pub mod extend_lock;
+pub mod initialize_config;
 pub mod initialize_vault;
+pub mod make_lock_permanent;
+pub mod merge_locks;
+pub mod migrate_account;
+pub mod mint_position_nft;
+pub mod quote_withdraw;
+pub mod refresh_lock_weight;
+pub mod remove_pool_from_allowlist;
+pub mod roll_over_lock;
+pub mod set_keeper;
+pub mod set_lock_policy;
+pub mod set_rollover;
+pub mod split_lock;
+pub mod transfer_lock;
+pub mod update_config;
 
 pub mod withdraw;
 
+pub use add_pool_to_allowlist::*;
+pub use add_reward::*;
+pub use batch_withdraw::*;
+pub use cast_vote::*;
+pub use claim_lp_fees::*;
+pub use claim_rewards::*;
+pub use create_pool_and_lock::*;
+pub use create_proposal::*;
 pub use deposit::*;
+pub use deposit_liquidity_and_lock::*;
+pub use execute_proposal::*;
+pub use extend_lock::*;
+pub use initialize_config::*;
 pub use initialize_vault::*;
+pub use make_lock_permanent::*;
+pub use merge_locks::*;
+pub use migrate_account::*;
+pub use mint_position_nft::*;
+pub use quote_withdraw::*;
+pub use refresh_lock_weight::*;
+pub use remove_pool_from_allowlist::*;
+pub use roll_over_lock::*;
+pub use set_keeper::*;
+pub use set_lock_policy::*;
+pub use set_rollover::*;
+pub use split_lock::*;
+pub use transfer_lock::*;
+pub use update_config::*;
 
 pub use withdraw::*;
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use raydium_cp_swap::states::PoolState;
+
+use crate::{utils::pool_amounts, Error, UserLock, Vault, WithdrawQuote, USER_LOCK_SEED};
+
+/// Read-only preview of `withdraw` for the same lock and amount, meant to be
+/// simulated. The quote is returned as the instruction's return data.
+pub fn handler(ctx: Context<QuoteWithdraw>, lp_token_amount: u64) -> Result<WithdrawQuote> {
+    let user_lock = &ctx.accounts.user_lock;
+    require!(
+        user_lock.amount >= lp_token_amount,
+        Error::InsufficientBalance
+    );
+
+    let (vault_0_amount, vault_1_amount, lp_supply) = pool_amounts(
+        &ctx.accounts.pool_state.load()?,
+        &ctx.accounts.token_0_vault,
+        &ctx.accounts.token_1_vault,
+    )?;
+
+    let mut quote = WithdrawQuote::new(
+        lp_token_amount,
+        vault_0_amount,
+        vault_1_amount,
+        lp_supply,
+        user_lock.deposit_basis_0,
+        user_lock.deposit_basis_1,
+        ctx.accounts.vault.fee_bps,
+    )?;
+    quote.apply_transfer_fees(
+        &ctx.accounts.vault_0_mint,
+        &ctx.accounts.vault_1_mint,
+        user_lock.keeper_tip_bps,
+    )?;
+
+    Ok(quote)
+}
+
+#[derive(Accounts)]
+pub struct QuoteWithdraw<'info> {
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    #[account(address = vault.pool_state)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(address = token_0_vault.mint)]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = token_1_vault.mint)]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+}
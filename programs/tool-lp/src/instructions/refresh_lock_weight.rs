@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+
+use crate::{LockWeightUpdatedEvent, UserLock, Vault, USER_LOCK_SEED};
+
+/// Permissionless checkpoint that lets a lock's weight decay with its remaining time.
+pub fn handler(ctx: Context<RefreshLockWeight>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let user_lock = &mut ctx.accounts.user_lock;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    vault.update_rewards(current_timestamp)?;
+    user_lock.settle_rewards(vault, current_timestamp)?;
+    user_lock.refresh_weight(vault, current_timestamp)?;
+    user_lock.reset_reward_debts(vault)?;
+
+    emit!(LockWeightUpdatedEvent {
+        user: user_lock.user,
+        vault: vault.key(),
+        weight: user_lock.weight,
+        total_weight: vault.total_weight,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RefreshLockWeight<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+}
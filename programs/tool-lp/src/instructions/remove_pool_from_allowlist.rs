@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::{Config, PoolAllowlistEntry, CONFIG_SEED, POOL_ALLOWLIST_SEED};
+
+pub fn handler(_ctx: Context<RemovePoolFromAllowlist>) -> Result<()> {
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RemovePoolFromAllowlist<'info> {
+    #[account(
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        close = admin,
+        seeds = [POOL_ALLOWLIST_SEED, pool_allowlist.pool_state.as_ref()],
+        bump = pool_allowlist.bump
+    )]
+    pub pool_allowlist: Account<'info, PoolAllowlistEntry>,
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::{Error, LockRolledOverEvent, UserLock, Vault, USER_LOCK_SEED};
+
+/// Permissionless crank that renews an expired rollover lock for another term.
+pub fn handler(ctx: Context<RollOverLock>) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let user_lock = &mut ctx.accounts.user_lock;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    require!(user_lock.amount > 0, Error::InsufficientBalance);
+
+    vault.update_rewards(current_timestamp)?;
+    user_lock.settle_rewards(vault, current_timestamp)?;
+
+    let previous_unlock_timestamp = user_lock.unlock_timestamp;
+    require!(
+        user_lock.roll_over(current_timestamp)?,
+        Error::RolloverNotDue
+    );
+    user_lock.refresh_weight(vault, current_timestamp)?;
+    user_lock.reset_reward_debts(vault)?;
+
+    emit!(LockRolledOverEvent {
+        user: user_lock.user,
+        vault: vault.key(),
+        previous_unlock_timestamp,
+        unlock_timestamp: user_lock.unlock_timestamp,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RollOverLock<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{Error, KeeperUpdatedEvent, UserLock, Vault, MAX_KEEPER_TIP_BPS, USER_LOCK_SEED};
+
+/// Lets keepers withdraw this lock to its owner once unlocked, for an optional tip.
+/// Can be bundled with the deposit that opens the lock. The authorization lapses
+/// once the position NFT changes hands.
+pub fn handler(ctx: Context<SetKeeper>, keeper_enabled: bool, keeper_tip_bps: u16) -> Result<()> {
+    let user_lock = &mut ctx.accounts.user_lock;
+
+    user_lock.assert_owner(
+        &ctx.accounts.user.key(),
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+    require!(keeper_tip_bps <= MAX_KEEPER_TIP_BPS, Error::FeeTooHigh);
+
+    user_lock.keeper_enabled = keeper_enabled;
+    user_lock.keeper_tip_bps = if keeper_enabled { keeper_tip_bps } else { 0 };
+    user_lock.keeper_authority = if keeper_enabled {
+        ctx.accounts.user.key()
+    } else {
+        Pubkey::default()
+    };
+
+    emit!(KeeperUpdatedEvent {
+        user: ctx.accounts.user.key(),
+        vault: ctx.accounts.vault.key(),
+        keeper_enabled,
+        keeper_tip_bps: user_lock.keeper_tip_bps,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetKeeper<'info> {
+    pub vault: Box<Account<'info, Vault>>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::{Config, Error, LockPolicy, Vault, CONFIG_SEED};
+
+pub fn handler(ctx: Context<SetLockPolicy>, lock_policy: LockPolicy) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let authority = ctx.accounts.authority.key();
+
+    require!(
+        authority == vault.creator || authority == ctx.accounts.config.admin,
+        Error::Unauthorized
+    );
+    lock_policy.validate()?;
+
+    vault.set_lock_policy(lock_policy);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetLockPolicy<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(seeds = [CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, Config>,
+    pub authority: Signer<'info>,
+}
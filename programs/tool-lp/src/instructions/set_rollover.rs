@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{Error, RolloverUpdatedEvent, UserLock, Vault, ROLLOVER_NOTICE_PERIOD, USER_LOCK_SEED};
+
+/// Turns automatic rollover on with the given term, or off with zero. Turning it
+/// off must happen at least `ROLLOVER_NOTICE_PERIOD` before the current term ends,
+/// so a term must be longer than that period or the lock could never opt out.
+pub fn handler(ctx: Context<SetRollover>, rollover_duration: i64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let user_lock = &mut ctx.accounts.user_lock;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    user_lock.assert_owner(
+        &ctx.accounts.user.key(),
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+    require!(!user_lock.is_permanent, Error::PermanentLock);
+    require!(user_lock.amount > 0, Error::InsufficientBalance);
+    require!(rollover_duration >= 0, Error::InvalidInput);
+
+    vault.update_rewards(current_timestamp)?;
+    user_lock.settle_rewards(vault, current_timestamp)?;
+
+    // Catch up on terms that already renewed before applying the change.
+    user_lock.roll_over(current_timestamp)?;
+    if rollover_duration > 0 {
+        require!(
+            rollover_duration > ROLLOVER_NOTICE_PERIOD,
+            Error::RolloverTermTooShort
+        );
+        vault.lock_policy.validate_duration(rollover_duration)?;
+    } else if user_lock.rollover_duration > 0 {
+        require!(
+            current_timestamp <= user_lock.unlock_timestamp - ROLLOVER_NOTICE_PERIOD,
+            Error::RolloverNoticeTooLate
+        );
+    }
+    user_lock.rollover_duration = rollover_duration;
+    user_lock.roll_over(current_timestamp)?;
+
+    user_lock.refresh_weight(vault, current_timestamp)?;
+    user_lock.reset_reward_debts(vault)?;
+
+    emit!(RolloverUpdatedEvent {
+        user: ctx.accounts.user.key(),
+        vault: vault.key(),
+        rollover_duration,
+        unlock_timestamp: user_lock.unlock_timestamp,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRollover<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+}
@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+
+use crate::{Error, LockSplitEvent, UserLock, Vault, USER_LOCK_SEED};
+
+/// Moves `amount` of a lock into the recipient's lock address. Lock addresses
+/// are keyed by vault and owner, so a wallet owns at most one untokenized lock
+/// per vault and cannot split its own lock to itself. A lock held through its
+/// position NFT keeps its creator's address and can be split to the holder.
+pub fn handler(ctx: Context<SplitLock>, amount: u64) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let source = &mut ctx.accounts.user_lock;
+    let destination = &mut ctx.accounts.new_user_lock;
+    let current_timestamp = Clock::get()?.unix_timestamp;
+
+    source.assert_owner(
+        &ctx.accounts.owner.key(),
+        ctx.accounts.position_token_account.as_deref(),
+    )?;
+    require_keys_neq!(source.key(), destination.key(), Error::InvalidInput);
+    require!(
+        amount > 0 && amount < source.amount,
+        Error::InsufficientBalance
+    );
+    require!(
+        destination.amount == 0 && destination.rewards_owed.iter().all(|owed| *owed == 0),
+        Error::DestinationLockNotEmpty
+    );
+
+    vault.update_rewards(current_timestamp)?;
+    source.settle_rewards(vault, current_timestamp)?;
+    destination.settle_rewards(vault, current_timestamp)?;
+    // Renew expired rollover terms first, so the split-off part does not start
+    // out unlocked while the source is still rolling.
+    source.roll_over(current_timestamp)?;
+
+    source.amount = source
+        .amount
+        .checked_sub(amount)
+        .ok_or(Error::ArithmeticError)?;
+    source.checkpoint_votes(current_timestamp);
+
+    // The split-off part keeps the per-LP basis and lock terms of the source.
+    destination.user = ctx.accounts.recipient.key();
+    destination.vault = vault.key();
+    destination.amount = amount;
+    destination.unlock_timestamp = source.unlock_timestamp;
+    destination.set_basis(source.deposit_basis_0, source.deposit_basis_1);
+    destination.last_deposit_timestamp = current_timestamp;
+    destination.vote_checkpoints = Default::default();
+    destination.checkpoint_votes(current_timestamp);
+    destination.is_permanent = source.is_permanent;
+    destination.rollover_duration = source.rollover_duration;
+    // A reused lock address must not keep the keepers its previous owner allowed.
+    destination.keeper_enabled = false;
+    destination.keeper_tip_bps = 0;
+    destination.keeper_authority = Pubkey::default();
+
+    source.refresh_weight(vault, current_timestamp)?;
+    destination.refresh_weight(vault, current_timestamp)?;
+    source.reset_reward_debts(vault)?;
+    destination.reset_reward_debts(vault)?;
+
+    emit!(LockSplitEvent {
+        vault: vault.key(),
+        source_lock: source.key(),
+        destination_lock: destination.key(),
+        recipient: destination.user,
+        amount,
+        unlock_timestamp: destination.unlock_timestamp,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SplitLock<'info> {
+    #[account(mut)]
+    pub vault: Box<Account<'info, Vault>>,
+    pub owner: Signer<'info>,
+    /// CHECK: Owner of the split-off lock; may be the signer
+    pub recipient: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Signer's position NFT account; required once the lock is tokenized
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserLock::LEN,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), recipient.key().as_ref()],
+        bump
+    )]
+    pub new_user_lock: Box<Account<'info, UserLock>>,
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+
+use crate::{Error, LockTransferredEvent, UserLock, Vault, USER_LOCK_SEED};
+
+/// Moves a lock without a position NFT to the new owner's lock address.
+/// Tokenized locks change hands with their NFT instead.
+pub fn handler(ctx: Context<TransferLock>) -> Result<()> {
+    let current_timestamp = Clock::get()?.unix_timestamp;
+    let source = &ctx.accounts.user_lock;
+    let new_owner = ctx.accounts.new_owner.key();
+
+    require!(!source.is_tokenized(), Error::AlreadyTokenized);
+    require!(source.amount > 0, Error::InsufficientBalance);
+    require_keys_neq!(new_owner, source.user, Error::InvalidInput);
+
+    let destination = &ctx.accounts.new_user_lock;
+    require!(
+        destination.amount == 0 && destination.rewards_owed.iter().all(|owed| *owed == 0),
+        Error::DestinationLockNotEmpty
+    );
+
+    // Amount, basis, unlock time, weight and reward checkpoints move as they are.
+    // Vote checkpoints start over so the moved lock cannot vote again on
+    // proposals opened before the transfer, and keepers must be re-authorized
+    // by the new owner.
+    let mut moved = UserLock {
+        user: new_owner,
+        vault: ctx.accounts.vault.key(),
+        last_deposit_timestamp: current_timestamp,
+        keeper_enabled: false,
+        keeper_tip_bps: 0,
+        keeper_authority: Pubkey::default(),
+        vote_checkpoints: Default::default(),
+        ..UserLock::clone(source)
+    };
+    moved.checkpoint_votes(current_timestamp);
+    let amount = moved.amount;
+    let unlock_timestamp = moved.unlock_timestamp;
+    ctx.accounts.new_user_lock.set_inner(moved);
+
+    emit!(LockTransferredEvent {
+        vault: ctx.accounts.vault.key(),
+        from: ctx.accounts.user.key(),
+        to: new_owner,
+        amount,
+        unlock_timestamp,
+        timestamp: current_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TransferLock<'info> {
+    pub vault: Box<Account<'info, Vault>>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: Receives the lock; does not need to sign
+    pub new_owner: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_lock: Box<Account<'info, UserLock>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserLock::LEN,
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_user_lock: Box<Account<'info, UserLock>>,
+    pub system_program: Program<'info, System>,
+}
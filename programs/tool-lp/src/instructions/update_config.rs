@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::{Config, CONFIG_SEED};
+
+pub fn handler(ctx: Context<UpdateConfig>, permissionless_vaults: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.permissionless_vaults = permissionless_vaults;
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin
+    )]
+    pub config: Account<'info, Config>,
+    pub admin: Signer<'info>,
+}
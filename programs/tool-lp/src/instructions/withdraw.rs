@@ -5,322 +5,617 @@ use anchor_spl::{
 };
 use raydium_cp_swap::{cpi, program::RaydiumCpSwap, states::PoolState};
 
-use crate::{Error, UserLock, Vault, WithdrawEvent, ADMIN_WALLET};
+use crate::{
+    math,
+    utils::{amount_after_transfer_fee, pool_amounts, PositionNft},
+    Error, KeeperTipPaidEvent, UserLock, Vault, WithdrawEvent, USER_LOCK_SEED,
+    VAULT_AUTHORITY_SEED,
+};
 
 pub fn handler(ctx: Context<Withdraw>, lp_token_amount: u64) -> Result<()> {
-    let vault = &mut ctx.accounts.vault;
-    let user_lock = &mut ctx.accounts.user_lock;
+    let accounts = ctx.accounts;
     let current_timestamp = Clock::get()?.unix_timestamp;
 
-    let (token_0_amount, token_1_amount, fee_0_amount, fee_1_amount) = {
-        let pool_state = ctx.accounts.pool_state.load()?;
+    let (owner, keeper_tip_bps) = accounts.authorize(lp_token_amount, current_timestamp)?;
+    let quote = accounts.quote(
+        lp_token_amount,
+        accounts.user_lock.deposit_basis_0,
+        accounts.user_lock.deposit_basis_1,
+    )?;
+    accounts.complete(
+        lp_token_amount,
+        &quote,
+        owner,
+        keeper_tip_bps,
+        current_timestamp,
+    )
+}
+
+/// Expected outcome of removing `lp_token_amount` of LP from a lock.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct WithdrawQuote {
+    pub lp_token_amount: u64,
+    /// Pool tokens the LP is worth, before any Token-2022 transfer fee.
+    pub raw_token_0_amount: u64,
+    pub raw_token_1_amount: u64,
+    /// What the LP was worth at the lock's per-LP basis.
+    pub deposit_token_0_amount: u64,
+    pub deposit_token_1_amount: u64,
+    /// Growth over the basis; zero when the position lost value.
+    pub growth_token_0_amount: u64,
+    pub growth_token_1_amount: u64,
+    /// Performance fee on the growth, in raw pool tokens.
+    pub fee_0_amount: u64,
+    pub fee_1_amount: u64,
+    /// Slippage floor: the raw amounts less the transfer fee into the vault.
+    pub minimum_token_0_amount: u64,
+    pub minimum_token_1_amount: u64,
+    /// What reaches the owner after the performance fee and all transfer fees,
+    /// when the owner signs.
+    pub net_token_0_amount: u64,
+    pub net_token_1_amount: u64,
+    /// Tip a keeper withdrawing the lock takes out of the owner's share before
+    /// its transfer fee; zero unless the lock offers one.
+    pub keeper_tip_0_amount: u64,
+    pub keeper_tip_1_amount: u64,
+}
+
+impl WithdrawQuote {
+    /// Quotes the pool-side amounts from the pool reserves and LP supply.
+    /// The transfer-fee dependent fields are left for `apply_transfer_fees`.
+    pub fn new(
+        lp_token_amount: u64,
+        vault_0_amount: u64,
+        vault_1_amount: u64,
+        lp_supply: u64,
+        deposit_basis_0: u128,
+        deposit_basis_1: u128,
+        fee_bps: u16,
+    ) -> Result<Self> {
+        let token_0 = math::split_withdrawal(
+            lp_token_amount,
+            vault_0_amount,
+            lp_supply,
+            deposit_basis_0,
+            fee_bps,
+        )
+        .map_err(Error::from)?;
+        let token_1 = math::split_withdrawal(
+            lp_token_amount,
+            vault_1_amount,
+            lp_supply,
+            deposit_basis_1,
+            fee_bps,
+        )
+        .map_err(Error::from)?;
+
+        Ok(Self {
+            lp_token_amount,
+            raw_token_0_amount: token_0.raw_amount,
+            raw_token_1_amount: token_1.raw_amount,
+            deposit_token_0_amount: token_0.deposit_amount,
+            deposit_token_1_amount: token_1.deposit_amount,
+            growth_token_0_amount: token_0.growth_amount,
+            growth_token_1_amount: token_1.growth_amount,
+            fee_0_amount: token_0.fee_amount,
+            fee_1_amount: token_1.fee_amount,
+            ..Default::default()
+        })
+    }
+
+    /// Fills in the slippage floor, net payouts and keeper tips for the pool's mints.
+    pub fn apply_transfer_fees(
+        &mut self,
+        vault_0_mint: &InterfaceAccount<Mint>,
+        vault_1_mint: &InterfaceAccount<Mint>,
+        keeper_tip_bps: u16,
+    ) -> Result<()> {
+        // Token-2022 transfer fees are withheld before Raydium's payout reaches the
+        // vault, so the slippage floor is what should actually arrive.
+        self.minimum_token_0_amount =
+            amount_after_transfer_fee(vault_0_mint, self.raw_token_0_amount)?;
+        self.minimum_token_1_amount =
+            amount_after_transfer_fee(vault_1_mint, self.raw_token_1_amount)?;
+
+        // Mirrors the split `Withdraw::execute` makes of what arrives.
+        let token_0_amount = self.minimum_token_0_amount
+            - math::share_of(
+                self.fee_0_amount,
+                self.raw_token_0_amount,
+                self.minimum_token_0_amount,
+            )
+            .map_err(Error::from)?;
+        let token_1_amount = self.minimum_token_1_amount
+            - math::share_of(
+                self.fee_1_amount,
+                self.raw_token_1_amount,
+                self.minimum_token_1_amount,
+            )
+            .map_err(Error::from)?;
+        self.net_token_0_amount = amount_after_transfer_fee(vault_0_mint, token_0_amount)?;
+        self.net_token_1_amount = amount_after_transfer_fee(vault_1_mint, token_1_amount)?;
+        self.keeper_tip_0_amount =
+            math::bps_of(token_0_amount, keeper_tip_bps).map_err(Error::from)?;
+        self.keeper_tip_1_amount =
+            math::bps_of(token_1_amount, keeper_tip_bps).map_err(Error::from)?;
+        Ok(())
+    }
+}
+
+/// Amounts actually sent out by `Withdraw::execute`.
+pub struct WithdrawPayout {
+    pub token_0_amount: u64,
+    pub token_1_amount: u64,
+    pub fee_0_amount: u64,
+    pub fee_1_amount: u64,
+    pub keeper_tip_0_amount: u64,
+    pub keeper_tip_1_amount: u64,
+}
 
+impl<'info> Withdraw<'info> {
+    /// Checks that the signer may withdraw `lp_token_amount` from the lock now.
+    /// Returns the lock owner and the keeper tip owed, zero when the owner signs.
+    pub(crate) fn authorize(
+        &self,
+        lp_token_amount: u64,
+        current_timestamp: i64,
+    ) -> Result<(Pubkey, u16)> {
+        // Once authorized by the owner, any keeper may withdraw on the owner's behalf.
+        // An authorization given by an earlier holder of the position NFT does not count.
+        let owner = self
+            .user_lock
+            .owner(self.position_token_account.as_deref())?;
+        let keeper_tip_bps = if self.user.key() == owner {
+            0
+        } else {
+            require!(
+                self.user_lock.keeper_enabled && self.user_lock.keeper_authority == owner,
+                Error::Unauthorized
+            );
+            self.user_lock.keeper_tip_bps
+        };
+        require!(!self.user_lock.is_permanent, Error::PermanentLock);
+        require!(self.user_lock.rollover_duration == 0, Error::RolloverActive);
         require!(
-            ctx.accounts.token_0_vault.key() == pool_state.token_0_vault,
-            Error::InvalidInput
+            current_timestamp >= self.user_lock.unlock_timestamp,
+            Error::LockNotYetExpired
         );
         require!(
-            ctx.accounts.token_1_vault.key() == pool_state.token_1_vault,
-            Error::InvalidInput
+            lp_token_amount > 0 && self.user_lock.amount >= lp_token_amount,
+            Error::InsufficientBalance
         );
+        // Emptying a tokenized lock burns its NFT, so the mint must be present.
+        if self.user_lock.is_tokenized() && lp_token_amount == self.user_lock.amount {
+            require_keys_eq!(
+                self.position_mint
+                    .as_ref()
+                    .ok_or(Error::InvalidInput)?
+                    .key(),
+                self.user_lock.position_mint,
+                Error::InvalidInput
+            );
+        }
+        Ok((owner, keeper_tip_bps))
+    }
+
+    /// Executes an authorized and quoted withdrawal and books it against the lock.
+    pub(crate) fn complete(
+        &mut self,
+        lp_token_amount: u64,
+        quote: &WithdrawQuote,
+        owner: Pubkey,
+        keeper_tip_bps: u16,
+        current_timestamp: i64,
+    ) -> Result<()> {
+        let payout = self.execute(lp_token_amount, quote, owner, keeper_tip_bps)?;
+
+        let vault = &mut self.vault;
+        let user_lock = &mut self.user_lock;
+        vault.update_rewards(current_timestamp)?;
+        user_lock.settle_rewards(vault, current_timestamp)?;
+
+        user_lock.amount = user_lock
+            .amount
+            .checked_sub(lp_token_amount)
+            .ok_or(Error::ArithmeticError)?;
+        user_lock.checkpoint_votes(current_timestamp);
+        vault.total_locked = vault
+            .total_locked
+            .checked_sub(lp_token_amount)
+            .ok_or(Error::ArithmeticError)?;
+        user_lock.refresh_weight(vault, current_timestamp)?;
+        user_lock.reset_reward_debts(vault)?;
+
+        // An empty lock is no longer represented by its NFT. Otherwise whoever
+        // holds it would own the next deposit into the lock.
+        if user_lock.amount == 0 && user_lock.is_tokenized() {
+            let position_nft = PositionNft {
+                mint: self
+                    .position_mint
+                    .as_ref()
+                    .ok_or(Error::InvalidInput)?
+                    .to_account_info(),
+                token_account: self
+                    .position_token_account
+                    .as_ref()
+                    .ok_or(Error::InvalidInput)?
+                    .to_account_info(),
+                vault_authority: self.vault_authority.to_account_info(),
+                token_2022_program: self.token_program_2022.to_account_info(),
+                associated_token_program: self.associated_token_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+            };
+            position_nft.retire(vault, user_lock, &self.user.to_account_info())?;
+        }
+
+        if payout.keeper_tip_0_amount > 0 || payout.keeper_tip_1_amount > 0 {
+            emit!(KeeperTipPaidEvent {
+                keeper: self.user.key(),
+                user: owner,
+                vault: vault.key(),
+                tip_0_amount: amount_after_transfer_fee(
+                    &self.vault_0_mint,
+                    payout.keeper_tip_0_amount,
+                )?,
+                tip_1_amount: amount_after_transfer_fee(
+                    &self.vault_1_mint,
+                    payout.keeper_tip_1_amount,
+                )?,
+                timestamp: current_timestamp,
+            });
+        }
+
+        emit!(WithdrawEvent {
+            user: owner,
+            vault: vault.key(),
+            pool_state: vault.pool_state,
+            lp_amount: lp_token_amount,
+            token_0_amount: amount_after_transfer_fee(&self.vault_0_mint, payout.token_0_amount)?,
+            token_1_amount: amount_after_transfer_fee(&self.vault_1_mint, payout.token_1_amount)?,
+            fee_0_amount: amount_after_transfer_fee(&self.vault_0_mint, payout.fee_0_amount)?,
+            fee_1_amount: amount_after_transfer_fee(&self.vault_1_mint, payout.fee_1_amount)?,
+            timestamp: current_timestamp,
+            user_lock: user_lock.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Checks the Raydium accounts against the pool state and returns the pool
+    /// reserves (excluding accrued protocol and fund fees) and LP supply, as
+    /// `quote_withdraw` reads them.
+    pub(crate) fn pool_amounts(&self) -> Result<(u64, u64, u64)> {
+        let pool_state = self.pool_state.load()?;
+
         require!(
-            ctx.accounts.lp_mint.key() == pool_state.lp_mint,
+            self.lp_mint.key() == pool_state.lp_mint,
             Error::InvalidInput
         );
         require!(
-            ctx.accounts.token_0_program.key() == pool_state.token_0_program,
+            self.token_0_program.key() == pool_state.token_0_program,
             Error::InvalidInput
         );
         require!(
-            ctx.accounts.token_1_program.key() == pool_state.token_1_program,
+            self.token_1_program.key() == pool_state.token_1_program,
             Error::InvalidInput
         );
 
-        require!(
-            current_timestamp >= user_lock.unlock_timestamp,
-            Error::LockNotYetExpired
-        );
-
-        require!(
-            user_lock.amount >= lp_token_amount,
-            Error::InsufficientBalance
-        );
+        pool_amounts(&pool_state, &self.token_0_vault, &self.token_1_vault)
+    }
 
-        let available_balance = ctx.accounts.vault_token_account.amount;
+    /// Quotes removing `lp_token_amount` of LP deposited at the given scaled per-LP basis.
+    /// The performance fee is charged on growth above that basis.
+    pub(crate) fn quote(
+        &self,
+        lp_token_amount: u64,
+        deposit_basis_0: u128,
+        deposit_basis_1: u128,
+    ) -> Result<WithdrawQuote> {
+        let (vault_0_amount, vault_1_amount, lp_supply) = self.pool_amounts()?;
+
+        let available_balance = self.vault_token_account.amount;
         require!(
             available_balance >= lp_token_amount,
             Error::InsufficientBalance
         );
 
-        let (vault_0_amount, vault_1_amount) = pool_state.vault_amount_without_fee(
-            ctx.accounts.token_0_vault.amount,
-            ctx.accounts.token_1_vault.amount,
+        let mut quote = WithdrawQuote::new(
+            lp_token_amount,
+            vault_0_amount,
+            vault_1_amount,
+            lp_supply,
+            deposit_basis_0,
+            deposit_basis_1,
+            self.vault.fee_bps,
+        )?;
+        quote.apply_transfer_fees(
+            &self.vault_0_mint,
+            &self.vault_1_mint,
+            self.user_lock.keeper_tip_bps,
+        )?;
+        Ok(quote)
+    }
+
+    /// Removes `lp_token_amount` of LP from the pool and splits what arrives
+    /// between `owner`, the fee recipient and, when tipped, the keeper.
+    pub(crate) fn execute(
+        &mut self,
+        lp_token_amount: u64,
+        quote: &WithdrawQuote,
+        owner: Pubkey,
+        keeper_tip_bps: u16,
+    ) -> Result<WithdrawPayout> {
+        // Proceeds only ever go to the lock owner, whoever signs.
+        require_keys_eq!(self.user_token_0_account.owner, owner, Error::InvalidInput);
+        require_keys_eq!(self.user_token_1_account.owner, owner, Error::InvalidInput);
+
+        let vault_key = self.vault.key();
+        let pool_state_key = self.pool_state.key();
+        let seeds = &[
+            VAULT_AUTHORITY_SEED,
+            pool_state_key.as_ref(),
+            vault_key.as_ref(),
+            &[self.vault.authority_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let cpi_accounts = cpi::accounts::Withdraw {
+            owner: self.vault_authority.to_account_info(),
+            authority: self.authority.to_account_info(),
+            pool_state: self.pool_state.to_account_info(),
+            owner_lp_token: self.vault_token_account.to_account_info(),
+            token_0_account: self.vault_token_0_account.to_account_info(),
+            token_1_account: self.vault_token_1_account.to_account_info(),
+            token_0_vault: self.token_0_vault.to_account_info(),
+            token_1_vault: self.token_1_vault.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            token_program_2022: self.token_program_2022.to_account_info(),
+            vault_0_mint: self.vault_0_mint.to_account_info(),
+            vault_1_mint: self.vault_1_mint.to_account_info(),
+            lp_mint: self.lp_mint.to_account_info(),
+            memo_program: self.memo_program.to_account_info(),
+        };
+
+        let cpi_context = CpiContext::new_with_signer(
+            self.cp_swap_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
         );
 
-        let raw_token_0_amount = lp_token_amount
-            .checked_mul(vault_0_amount)
-            .ok_or(Error::ArithmeticError)?
-            .checked_div(pool_state.lp_supply)
-            .ok_or(Error::ArithmeticError)?;
+        let vault_token_0_balance = self.vault_token_0_account.amount;
+        let vault_token_1_balance = self.vault_token_1_account.amount;
 
-        let raw_token_1_amount = lp_token_amount
-            .checked_mul(vault_1_amount)
-            .ok_or(Error::ArithmeticError)?
-            .checked_div(pool_state.lp_supply)
-            .ok_or(Error::ArithmeticError)?;
+        cpi::withdraw(
+            cpi_context,
+            lp_token_amount,
+            quote.minimum_token_0_amount,
+            quote.minimum_token_1_amount,
+        )?;
 
-        let deposit_token_0_amount = lp_token_amount
-            .checked_mul(user_lock.deposit_token_per_lp_0)
+        // Only what this CPI delivered is paid out; leftovers from earlier
+        // withdrawals stay in the vault accounts instead of going to this user.
+        self.vault_token_0_account.reload()?;
+        self.vault_token_1_account.reload()?;
+        let received_token_0_amount = self
+            .vault_token_0_account
+            .amount
+            .checked_sub(vault_token_0_balance)
             .ok_or(Error::ArithmeticError)?;
-
-        let deposit_token_1_amount = lp_token_amount
-            .checked_mul(user_lock.deposit_token_per_lp_1)
+        let received_token_1_amount = self
+            .vault_token_1_account
+            .amount
+            .checked_sub(vault_token_1_balance)
             .ok_or(Error::ArithmeticError)?;
 
-        let growth_0 = (raw_token_0_amount as i128)
-            .checked_sub(deposit_token_0_amount as i128)
+        let fee_0_amount = math::share_of(
+            quote.fee_0_amount,
+            quote.raw_token_0_amount,
+            received_token_0_amount,
+        )
+        .map_err(Error::from)?;
+        let fee_1_amount = math::share_of(
+            quote.fee_1_amount,
+            quote.raw_token_1_amount,
+            received_token_1_amount,
+        )
+        .map_err(Error::from)?;
+        let mut token_0_amount = received_token_0_amount
+            .checked_sub(fee_0_amount)
             .ok_or(Error::ArithmeticError)?;
-
-        let growth_1 = (raw_token_1_amount as i128)
-            .checked_sub(deposit_token_1_amount as i128)
+        let mut token_1_amount = received_token_1_amount
+            .checked_sub(fee_1_amount)
             .ok_or(Error::ArithmeticError)?;
 
-        let fee_0_amount = if growth_0 > 0 {
-            (growth_0 as u64)
-                .checked_mul(20)
-                .ok_or(Error::ArithmeticError)?
-                .checked_div(100)
-                .ok_or(Error::ArithmeticError)?
+        // The tip comes out of the owner's share and is skipped when the keeper
+        // brings no accounts to receive it.
+        let (keeper_tip_0_amount, keeper_tip_1_amount) = if keeper_tip_bps > 0
+            && self.keeper_token_0_account.is_some()
+            && self.keeper_token_1_account.is_some()
+        {
+            (
+                math::bps_of(token_0_amount, keeper_tip_bps).map_err(Error::from)?,
+                math::bps_of(token_1_amount, keeper_tip_bps).map_err(Error::from)?,
+            )
         } else {
-            0
+            (0, 0)
         };
-
-        let fee_1_amount = if growth_1 > 0 {
-            (growth_1 as u64)
-                .checked_mul(20)
-                .ok_or(Error::ArithmeticError)?
-                .checked_div(100)
-                .ok_or(Error::ArithmeticError)?
-        } else {
-            0
-        };
-
-        let token_0_amount = deposit_token_0_amount
-            .checked_add(if growth_0 > 0 {
-                (growth_0 as u64).checked_sub(fee_0_amount).unwrap_or(0)
-            } else {
-                0
-            })
-            .ok_or(Error::ArithmeticError)?;
-
-        let token_1_amount = deposit_token_1_amount
-            .checked_add(if growth_1 > 0 {
-                (growth_1 as u64).checked_sub(fee_1_amount).unwrap_or(0)
-            } else {
-                0
-            })
-            .ok_or(Error::ArithmeticError)?;
-
-        (token_0_amount, token_1_amount, fee_0_amount, fee_1_amount)
-    };
-
-    let vault_key = vault.key();
-    let pool_state_key = ctx.accounts.pool_state.key();
-    let seeds = &[
-        b"vault-authority",
-        pool_state_key.as_ref(),
-        vault_key.as_ref(),
-        &[vault.bump],
-    ];
-    let signer_seeds = &[&seeds[..]];
-
-    let cpi_accounts = cpi::accounts::Withdraw {
-        owner: ctx.accounts.vault_authority.to_account_info(),
-        authority: ctx.accounts.authority.to_account_info(),
-        pool_state: ctx.accounts.pool_state.to_account_info(),
-        owner_lp_token: ctx.accounts.vault_token_account.to_account_info(),
-        token_0_account: ctx.accounts.vault_token_0_account.to_account_info(),
-        token_1_account: ctx.accounts.vault_token_1_account.to_account_info(),
-        token_0_vault: ctx.accounts.token_0_vault.to_account_info(),
-        token_1_vault: ctx.accounts.token_1_vault.to_account_info(),
-        token_program: ctx.accounts.token_program.to_account_info(),
-        token_program_2022: ctx.accounts.token_program_2022.to_account_info(),
-        vault_0_mint: ctx.accounts.vault_0_mint.to_account_info(),
-        vault_1_mint: ctx.accounts.vault_1_mint.to_account_info(),
-        lp_mint: ctx.accounts.lp_mint.to_account_info(),
-        memo_program: ctx.accounts.memo_program.to_account_info(),
-    };
-
-    let cpi_context = CpiContext::new_with_signer(
-        ctx.accounts.cp_swap_program.to_account_info(),
-        cpi_accounts,
-        signer_seeds,
-    );
-
-    cpi::withdraw(cpi_context, lp_token_amount, token_0_amount, token_1_amount)?;
-
-    if token_0_amount > 0 {
-        token_interface::transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_0_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.vault_token_0_account.to_account_info(),
-                    to: ctx.accounts.user_token_0_account.to_account_info(),
-                    mint: ctx.accounts.vault_0_mint.to_account_info(),
-                    authority: ctx.accounts.vault_authority.to_account_info(),
-                },
-                signer_seeds,
-            ),
+        token_0_amount -= keeper_tip_0_amount;
+        token_1_amount -= keeper_tip_1_amount;
+
+        if token_0_amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_0_program.to_account_info(),
+                    TransferChecked {
+                        from: self.vault_token_0_account.to_account_info(),
+                        to: self.user_token_0_account.to_account_info(),
+                        mint: self.vault_0_mint.to_account_info(),
+                        authority: self.vault_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                token_0_amount,
+                self.vault_0_mint.decimals,
+            )?;
+        }
+
+        if token_1_amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_1_program.to_account_info(),
+                    TransferChecked {
+                        from: self.vault_token_1_account.to_account_info(),
+                        to: self.user_token_1_account.to_account_info(),
+                        mint: self.vault_1_mint.to_account_info(),
+                        authority: self.vault_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                token_1_amount,
+                self.vault_1_mint.decimals,
+            )?;
+        }
+
+        if fee_0_amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_0_program.to_account_info(),
+                    TransferChecked {
+                        from: self.vault_token_0_account.to_account_info(),
+                        to: self.fee_recipient_token_0_account.to_account_info(),
+                        mint: self.vault_0_mint.to_account_info(),
+                        authority: self.vault_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee_0_amount,
+                self.vault_0_mint.decimals,
+            )?;
+        }
+
+        if fee_1_amount > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new_with_signer(
+                    self.token_1_program.to_account_info(),
+                    TransferChecked {
+                        from: self.vault_token_1_account.to_account_info(),
+                        to: self.fee_recipient_token_1_account.to_account_info(),
+                        mint: self.vault_1_mint.to_account_info(),
+                        authority: self.vault_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                fee_1_amount,
+                self.vault_1_mint.decimals,
+            )?;
+        }
+
+        if let Some(keeper_token_0_account) = &self.keeper_token_0_account {
+            if keeper_tip_0_amount > 0 {
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        self.token_0_program.to_account_info(),
+                        TransferChecked {
+                            from: self.vault_token_0_account.to_account_info(),
+                            to: keeper_token_0_account.to_account_info(),
+                            mint: self.vault_0_mint.to_account_info(),
+                            authority: self.vault_authority.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    keeper_tip_0_amount,
+                    self.vault_0_mint.decimals,
+                )?;
+            }
+        }
+
+        if let Some(keeper_token_1_account) = &self.keeper_token_1_account {
+            if keeper_tip_1_amount > 0 {
+                token_interface::transfer_checked(
+                    CpiContext::new_with_signer(
+                        self.token_1_program.to_account_info(),
+                        TransferChecked {
+                            from: self.vault_token_1_account.to_account_info(),
+                            to: keeper_token_1_account.to_account_info(),
+                            mint: self.vault_1_mint.to_account_info(),
+                            authority: self.vault_authority.to_account_info(),
+                        },
+                        signer_seeds,
+                    ),
+                    keeper_tip_1_amount,
+                    self.vault_1_mint.decimals,
+                )?;
+            }
+        }
+
+        Ok(WithdrawPayout {
             token_0_amount,
-            ctx.accounts.vault_0_mint.decimals,
-        )?;
-    }
-
-    if token_1_amount > 0 {
-        token_interface::transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_1_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.vault_token_1_account.to_account_info(),
-                    to: ctx.accounts.user_token_1_account.to_account_info(),
-                    mint: ctx.accounts.vault_1_mint.to_account_info(),
-                    authority: ctx.accounts.vault_authority.to_account_info(),
-                },
-                signer_seeds,
-            ),
             token_1_amount,
-            ctx.accounts.vault_1_mint.decimals,
-        )?;
-    }
-
-    if ctx
-        .accounts
-        .admin_token_0_account
-        .to_account_info()
-        .data_is_empty()
-    {
-        anchor_spl::associated_token::create(CpiContext::new(
-            ctx.accounts.associated_token_program.to_account_info(),
-            anchor_spl::associated_token::Create {
-                payer: ctx.accounts.user.to_account_info(),
-                associated_token: ctx.accounts.admin_token_0_account.to_account_info(),
-                authority: ctx.accounts.admin.to_account_info(),
-                mint: ctx.accounts.vault_0_mint.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-                token_program: ctx.accounts.token_0_program.to_account_info(),
-            },
-        ))?;
-    }
-
-    if ctx
-        .accounts
-        .admin_token_1_account
-        .to_account_info()
-        .data_is_empty()
-    {
-        anchor_spl::associated_token::create(CpiContext::new(
-            ctx.accounts.associated_token_program.to_account_info(),
-            anchor_spl::associated_token::Create {
-                payer: ctx.accounts.user.to_account_info(),
-                associated_token: ctx.accounts.admin_token_1_account.to_account_info(),
-                authority: ctx.accounts.admin.to_account_info(),
-                mint: ctx.accounts.vault_1_mint.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-                token_program: ctx.accounts.token_1_program.to_account_info(),
-            },
-        ))?;
-    }
-
-    if fee_0_amount > 0 {
-        token_interface::transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_0_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.vault_token_0_account.to_account_info(),
-                    to: ctx.accounts.admin_token_0_account.to_account_info(),
-                    mint: ctx.accounts.vault_0_mint.to_account_info(),
-                    authority: ctx.accounts.vault_authority.to_account_info(),
-                },
-                signer_seeds,
-            ),
             fee_0_amount,
-            ctx.accounts.vault_0_mint.decimals,
-        )?;
-    }
-
-    if fee_1_amount > 0 {
-        token_interface::transfer_checked(
-            CpiContext::new_with_signer(
-                ctx.accounts.token_1_program.to_account_info(),
-                TransferChecked {
-                    from: ctx.accounts.vault_token_1_account.to_account_info(),
-                    to: ctx.accounts.admin_token_1_account.to_account_info(),
-                    mint: ctx.accounts.vault_1_mint.to_account_info(),
-                    authority: ctx.accounts.vault_authority.to_account_info(),
-                },
-                signer_seeds,
-            ),
             fee_1_amount,
-            ctx.accounts.vault_1_mint.decimals,
-        )?;
+            keeper_tip_0_amount,
+            keeper_tip_1_amount,
+        })
     }
-
-    user_lock.amount = user_lock
-        .amount
-        .checked_sub(lp_token_amount)
-        .ok_or(Error::ArithmeticError)?;
-    vault.total_locked = vault
-        .total_locked
-        .checked_sub(lp_token_amount)
-        .ok_or(Error::ArithmeticError)?;
-
-    emit!(WithdrawEvent {
-        user: ctx.accounts.user.key(),
-        vault: vault.key(),
-        pool_state: vault.pool_state,
-        lp_amount: lp_token_amount,
-        token_0_amount,
-        token_1_amount,
-        fee_0_amount,
-        fee_1_amount,
-        timestamp: current_timestamp,
-    });
-
-    Ok(())
 }
 
 #[derive(Accounts)]
-#[instruction(lp_token_amount: u64)]
 pub struct Withdraw<'info> {
     #[account(mut, has_one = token_mint)]
-    pub vault: Account<'info, Vault>,
+    pub vault: Box<Account<'info, Vault>>,
+    /// Lock owner, or a keeper once the owner has enabled keepers. Pays for the
+    /// fee recipient token accounts unless `payer` is given
     #[account(mut)]
     pub user: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"user-lock", vault.key().as_ref(), user.key().as_ref()],
+        seeds = [USER_LOCK_SEED, vault.key().as_ref(), user_lock.user.as_ref()],
         bump
     )]
-    pub user_lock: Account<'info, UserLock>,
+    pub user_lock: Box<Account<'info, UserLock>>,
+    /// Position NFT holder's account; required once the lock is tokenized
+    #[account(mut)]
+    pub position_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Required when the withdrawal empties a tokenized lock, whose NFT is burned
+    #[account(mut)]
+    pub position_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
     #[account(mut, token::mint = token_mint, token::authority = vault_authority)]
     pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
     #[account(mut, token::mint = vault_0_mint, token::authority = vault_authority)]
     pub vault_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
     #[account(mut, token::mint = vault_1_mint, token::authority = vault_authority)]
     pub vault_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
-    #[account(mut, token::mint = vault_0_mint, token::authority = user)]
+    /// Lock owner's account
+    #[account(mut, token::mint = vault_0_mint)]
     pub user_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
-    #[account(mut, token::mint = vault_1_mint, token::authority = user)]
+    /// Lock owner's account
+    #[account(mut, token::mint = vault_1_mint)]
     pub user_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
-    #[account(address = ADMIN_WALLET)]
-    /// CHECK: Hardcoded admin wallet from lib.rs
-    pub admin: UncheckedAccount<'info>,
-    /// CHECK: will be created if empty
-    #[account(mut)]
-    pub admin_token_0_account: UncheckedAccount<'info>,
-    /// CHECK: will be created if empty
-    #[account(mut)]
-    pub admin_token_1_account: UncheckedAccount<'info>,
+    /// Receives the keeper tip; omit to forgo it
+    #[account(mut, token::mint = vault_0_mint, token::authority = user)]
+    pub keeper_token_0_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Receives the keeper tip; omit to forgo it
+    #[account(mut, token::mint = vault_1_mint, token::authority = user)]
+    pub keeper_token_1_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(address = vault.fee_recipient)]
+    /// CHECK: Fee recipient configured on the vault
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = payer.as_ref().unwrap_or(&user),
+        associated_token::mint = vault_0_mint,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_0_program
+    )]
+    pub fee_recipient_token_0_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = payer.as_ref().unwrap_or(&user),
+        associated_token::mint = vault_1_mint,
+        associated_token::authority = fee_recipient,
+        associated_token::token_program = token_1_program
+    )]
+    pub fee_recipient_token_1_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(mut)]
     pub token_0_vault: Box<InterfaceAccount<'info, TokenAccount>>,
@@ -328,21 +623,21 @@ pub struct Withdraw<'info> {
     pub token_1_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
-        seeds = [b"vault-authority", vault.pool_state.as_ref(), vault.key().as_ref()],
-        bump = vault.bump
+        seeds = [VAULT_AUTHORITY_SEED, vault.pool_state.as_ref(), vault.key().as_ref()],
+        bump = vault.authority_bump
     )]
     /// CHECK: PDA verified via seeds
     pub vault_authority: UncheckedAccount<'info>,
     #[account(mut, address = vault.pool_state)]
     pub pool_state: AccountLoader<'info, PoolState>,
     pub cp_swap_program: Program<'info, RaydiumCpSwap>,
-    #[account(  
-        seeds = [  
+    #[account(
+        seeds = [
             raydium_cp_swap::AUTH_SEED.as_bytes(),
-        ],  
-        seeds::program = cp_swap_program,  
-        bump,  
-    )]  
+        ],
+        seeds::program = cp_swap_program,
+        bump,
+    )]
     /// CHECK: pool vault and lp mint authority
     pub authority: UncheckedAccount<'info>,
     pub token_mint: InterfaceAccount<'info, Mint>,
@@ -363,4 +658,7 @@ pub struct Withdraw<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub clock: Sysvar<'info, Clock>,
+    /// Pays for the fee recipient token accounts instead of `user`; kept last so it can be omitted
+    #[account(mut)]
+    pub payer: Option<Signer<'info>>,
 }
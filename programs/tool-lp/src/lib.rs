@@ -1,29 +1,260 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
 
 pub mod instructions;
+pub mod utils;
 use instructions::*;
+pub use tool_lp_math as math;
+pub use tool_lp_math::{BPS_DENOMINATOR, TOKEN_PER_LP_SCALE};
 
 declare_id!("DduTe3VFPwWGN2EBh8FZ1GSnXe7VFotp1A8eej7qwgX2");
 
 pub const ADMIN_WALLET: Pubkey = pubkey!("As1T4LoB97vriM5HWXy2Z23s8Sp9ymZgibnnc2r9mCQZ");
 
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const VAULT_TOKEN_SEED: &[u8] = b"vault-token";
+pub const VAULT_AUTHORITY_SEED: &[u8] = b"vault-authority";
+pub const USER_LOCK_SEED: &[u8] = b"user-lock";
+pub const CONFIG_SEED: &[u8] = b"config";
+pub const POOL_ALLOWLIST_SEED: &[u8] = b"pool-allowlist";
+pub const REWARD_VAULT_SEED: &[u8] = b"reward-vault";
+pub const PROPOSAL_SEED: &[u8] = b"proposal";
+pub const VOTE_RECORD_SEED: &[u8] = b"vote-record";
+pub const POSITION_MINT_SEED: &[u8] = b"position-mint";
+
+pub const POSITION_NAME: &str = "LP Lock Position";
+pub const POSITION_SYMBOL: &str = "LPLOCK";
+
+/// Share of LP growth taken as performance fee by new vaults.
+pub const DEFAULT_FEE_BPS: u16 = 2_000;
+pub const MAX_FEE_BPS: u16 = 5_000;
+/// Largest share of a withdrawal a lock owner can offer to the keeper executing it.
+pub const MAX_KEEPER_TIP_BPS: u16 = 100;
+pub const PROPOSAL_VOTING_PERIOD: i64 = 3 * 24 * 60 * 60;
+/// Share of the LP locked at proposal creation that must vote for a result to count.
+pub const PROPOSAL_QUORUM_BPS: u64 = 2_000;
+
+pub const MAX_DURATION_PRESETS: usize = 4;
+pub const MAX_REWARDS: usize = 3;
+/// How long after a reward stream ends its slot is kept for lockers to claim
+/// before `add_reward` may reuse it.
+pub const REWARD_CLAIM_PERIOD: i64 = 90 * 24 * 60 * 60;
+/// Lock duration that earns full weight when the vault policy sets no maximum.
+pub const DEFAULT_MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60;
+/// Fixed-point scale of `RewardInfo::reward_per_weight`.
+pub const REWARD_PER_WEIGHT_SCALE: u128 = 1_000_000_000_000;
+/// Slack allowed below a duration preset for the time between signing and execution.
+pub const DURATION_PRESET_TOLERANCE: i64 = 600;
+/// Unlock time recorded on permanent locks.
+pub const PERMANENT_UNLOCK_TIMESTAMP: i64 = i64::MAX;
+/// Remaining accounts `batch_withdraw` takes once: the `Withdraw` accounts that
+/// do not depend on the lock.
+pub const WITHDRAW_SHARED_ACCOUNTS: usize = 29;
+/// Remaining accounts `batch_withdraw` takes per position: `user_lock`,
+/// `position_token_account`, `position_mint`, `user_token_0_account` and
+/// `user_token_1_account`.
+pub const WITHDRAW_ACCOUNTS_PER_POSITION: usize = 5;
+/// How long before expiry a rollover lock must opt out to be withdrawable at that expiry.
+pub const ROLLOVER_NOTICE_PERIOD: i64 = 7 * 24 * 60 * 60;
+
 #[program]
 pub mod tool_lp {
     use super::*;
 
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        instructions::initialize_config::handler(ctx)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, permissionless_vaults: bool) -> Result<()> {
+        instructions::update_config::handler(ctx, permissionless_vaults)
+    }
+
+    pub fn add_pool_to_allowlist(ctx: Context<AddPoolToAllowlist>) -> Result<()> {
+        instructions::add_pool_to_allowlist::handler(ctx)
+    }
+
+    pub fn remove_pool_from_allowlist(ctx: Context<RemovePoolFromAllowlist>) -> Result<()> {
+        instructions::remove_pool_from_allowlist::handler(ctx)
+    }
+
     pub fn initialize_vault(ctx: Context<InitializeVault>) -> Result<()> {
         instructions::initialize_vault::handler(ctx)
     }
 
+    pub fn create_pool_and_lock(
+        ctx: Context<CreatePoolAndLock>,
+        init_amount_0: u64,
+        init_amount_1: u64,
+        open_time: u64,
+        lock_policy: LockPolicy,
+        unlock_timestamp: i64,
+    ) -> Result<()> {
+        instructions::create_pool_and_lock::handler(
+            ctx,
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            lock_policy,
+            unlock_timestamp,
+        )
+    }
+
     pub fn deposit(ctx: Context<Deposit>, amount: u64, unlock_timestamp: i64) -> Result<()> {
         instructions::deposit::handler(ctx, amount, unlock_timestamp)
     }
 
+    pub fn deposit_liquidity_and_lock(
+        ctx: Context<DepositLiquidityAndLock>,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        minimum_lp_amount: u64,
+        unlock_timestamp: i64,
+    ) -> Result<()> {
+        instructions::deposit_liquidity_and_lock::handler(
+            ctx,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+            minimum_lp_amount,
+            unlock_timestamp,
+        )
+    }
+
     pub fn withdraw(ctx: Context<Withdraw>, lp_token_amount: u64) -> Result<()> {
         instructions::withdraw::handler(ctx, lp_token_amount)
     }
+
+    pub fn quote_withdraw(
+        ctx: Context<QuoteWithdraw>,
+        lp_token_amount: u64,
+    ) -> Result<WithdrawQuote> {
+        instructions::quote_withdraw::handler(ctx, lp_token_amount)
+    }
+
+    pub fn batch_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchWithdraw<'info>>,
+        skip_failed: bool,
+    ) -> Result<()> {
+        instructions::batch_withdraw::handler(ctx, skip_failed)
+    }
+
+    pub fn set_lock_policy(ctx: Context<SetLockPolicy>, lock_policy: LockPolicy) -> Result<()> {
+        instructions::set_lock_policy::handler(ctx, lock_policy)
+    }
+
+    pub fn extend_lock(ctx: Context<ExtendLock>, unlock_timestamp: i64) -> Result<()> {
+        instructions::extend_lock::handler(ctx, unlock_timestamp)
+    }
+
+    pub fn add_reward(
+        ctx: Context<AddReward>,
+        emissions_per_second: u64,
+        start_timestamp: i64,
+        end_timestamp: i64,
+    ) -> Result<()> {
+        instructions::add_reward::handler(ctx, emissions_per_second, start_timestamp, end_timestamp)
+    }
+
+    pub fn claim_rewards(ctx: Context<ClaimRewards>, reward_index: u8) -> Result<()> {
+        instructions::claim_rewards::handler(ctx, reward_index)
+    }
+
+    pub fn migrate_account(ctx: Context<MigrateAccount>) -> Result<()> {
+        instructions::migrate_account::handler(ctx)
+    }
+
+    pub fn refresh_lock_weight(ctx: Context<RefreshLockWeight>) -> Result<()> {
+        instructions::refresh_lock_weight::handler(ctx)
+    }
+
+    pub fn mint_position_nft(ctx: Context<MintPositionNft>, uri: String) -> Result<()> {
+        instructions::mint_position_nft::handler(ctx, uri)
+    }
+
+    pub fn transfer_lock(ctx: Context<TransferLock>) -> Result<()> {
+        instructions::transfer_lock::handler(ctx)
+    }
+
+    pub fn split_lock(ctx: Context<SplitLock>, amount: u64) -> Result<()> {
+        instructions::split_lock::handler(ctx, amount)
+    }
+
+    pub fn merge_locks(ctx: Context<MergeLocks>) -> Result<()> {
+        instructions::merge_locks::handler(ctx)
+    }
+
+    pub fn set_keeper(
+        ctx: Context<SetKeeper>,
+        keeper_enabled: bool,
+        keeper_tip_bps: u16,
+    ) -> Result<()> {
+        instructions::set_keeper::handler(ctx, keeper_enabled, keeper_tip_bps)
+    }
+
+    pub fn set_rollover(ctx: Context<SetRollover>, rollover_duration: i64) -> Result<()> {
+        instructions::set_rollover::handler(ctx, rollover_duration)
+    }
+
+    pub fn roll_over_lock(ctx: Context<RollOverLock>) -> Result<()> {
+        instructions::roll_over_lock::handler(ctx)
+    }
+
+    pub fn make_lock_permanent(ctx: Context<MakeLockPermanent>) -> Result<()> {
+        instructions::make_lock_permanent::handler(ctx)
+    }
+
+    pub fn claim_lp_fees(ctx: Context<Withdraw>) -> Result<()> {
+        instructions::claim_lp_fees::handler(ctx)
+    }
+
+    pub fn create_proposal(ctx: Context<CreateProposal>, kind: ProposalKind) -> Result<()> {
+        instructions::create_proposal::handler(ctx, kind)
+    }
+
+    pub fn cast_vote(ctx: Context<CastVote>, support: bool) -> Result<()> {
+        instructions::cast_vote::handler(ctx, support)
+    }
+
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        instructions::execute_proposal::handler(ctx)
+    }
+}
+
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    pub permissionless_vaults: bool,
+    pub bump: u8,
+}
+
+impl Config {
+    pub const LEN: usize = 32 + 1 + 1;
+
+    /// Whether anyone may create a vault, read from the config PDA. Until
+    /// `initialize_config` has run there is no config and vault creation
+    /// stays permissionless.
+    pub fn allows_permissionless_vaults(config: &AccountInfo) -> Result<bool> {
+        if config.data_is_empty() {
+            return Ok(true);
+        }
+        require_keys_eq!(*config.owner, ID, Error::InvalidInput);
+        let config = Config::try_deserialize(&mut &config.try_borrow_data()?[..])?;
+        Ok(config.permissionless_vaults)
+    }
+}
+
+/// Allowlists a single pool. Pools are allowlisted one by one; there is no
+/// policy that admits every pool of a token pair.
+#[account]
+pub struct PoolAllowlistEntry {
+    pub pool_state: Pubkey,
+    pub bump: u8,
 }
 
+impl PoolAllowlistEntry {
+    pub const LEN: usize = 32 + 1;
+}
+
+/// New fields go at the end, so `migrate_account` can grow older accounts.
 #[account]
 pub struct Vault {
     pub pool_state: Pubkey,
@@ -31,28 +262,636 @@ pub struct Vault {
     pub vault_token_account: Pubkey,
     pub total_locked: u64,
     pub bump: u8,
+    pub creator: Pubkey,
+    pub lock_policy: LockPolicy,
+    pub authority_bump: u8,
+    pub rewards: [RewardInfo; MAX_REWARDS],
+    /// Sum of `UserLock::weight`; reward emissions are shared by weight.
+    pub total_weight: u64,
+    pub fee_bps: u16,
+    pub fee_recipient: Pubkey,
+    pub proposal_count: u64,
+    /// LP held by permanent locks, which can never be withdrawn.
+    pub total_permanent_locked: u64,
+    /// Bumped each time a reward slot gets a new stream.
+    pub reward_generations: [u32; MAX_REWARDS],
+    /// Remaining lock time that earns full weight. Follows the lock policy's
+    /// maximum, but only while no lock carries weight computed against it.
+    pub weight_duration: i64,
+    /// Rewards forfeited by locks whose weight had decayed below the stored
+    /// one; shared out again with the stream's next emissions.
+    pub undistributed_rewards: [u64; MAX_REWARDS],
 }
 
 impl Vault {
-    pub const LEN: usize = 32 + 32 + 32 + 8 + 1;
+    pub const LEN: usize = 32
+        + 32
+        + 32
+        + 8
+        + 1
+        + 32
+        + LockPolicy::LEN
+        + 1
+        + RewardInfo::LEN * MAX_REWARDS
+        + 8
+        + 2
+        + 32
+        + 8
+        + 8
+        + 4 * MAX_REWARDS
+        + 8
+        + 8 * MAX_REWARDS;
+
+    /// Sets up a vault with an open lock policy and the default performance fee.
+    pub fn initialize(
+        &mut self,
+        pool_state: Pubkey,
+        token_mint: Pubkey,
+        vault_token_account: Pubkey,
+        creator: Pubkey,
+        bump: u8,
+        authority_bump: u8,
+    ) {
+        self.pool_state = pool_state;
+        self.token_mint = token_mint;
+        self.vault_token_account = vault_token_account;
+        self.total_locked = 0;
+        self.bump = bump;
+        self.creator = creator;
+        self.lock_policy = LockPolicy::default();
+        self.authority_bump = authority_bump;
+        self.rewards = Default::default();
+        self.total_weight = 0;
+        self.fee_bps = DEFAULT_FEE_BPS;
+        self.fee_recipient = ADMIN_WALLET;
+        self.proposal_count = 0;
+        self.total_permanent_locked = 0;
+        self.reward_generations = [0; MAX_REWARDS];
+        self.weight_duration = self.max_lock_duration();
+        self.undistributed_rewards = [0; MAX_REWARDS];
+    }
+
+    /// Longest lock the policy allows, or `DEFAULT_MAX_LOCK_DURATION` without one.
+    pub fn max_lock_duration(&self) -> i64 {
+        if self.lock_policy.max_duration > 0 {
+            self.lock_policy.max_duration
+        } else {
+            DEFAULT_MAX_LOCK_DURATION
+        }
+    }
+
+    /// Replaces the lock policy. `weight_duration` only follows a new maximum
+    /// while no lock carries weight, since stored weights would otherwise be
+    /// on two different scales.
+    pub fn set_lock_policy(&mut self, lock_policy: LockPolicy) {
+        self.lock_policy = lock_policy;
+        if self.total_weight == 0 {
+            self.weight_duration = self.max_lock_duration();
+        }
+    }
+
+    /// Accrues every active reward stream up to `current_timestamp`. While no
+    /// lock carries weight nothing is emitted; the stream ends that much later
+    /// instead, so its funding is still paid out in full, at the same rate.
+    pub fn update_rewards(&mut self, current_timestamp: i64) -> Result<()> {
+        let total_weight = self.total_weight;
+        for (reward, undistributed) in self
+            .rewards
+            .iter_mut()
+            .zip(self.undistributed_rewards.iter_mut())
+            .filter(|(reward, _)| reward.is_initialized())
+        {
+            let from = reward.last_update_timestamp.max(reward.start_timestamp);
+            let to = current_timestamp.min(reward.end_timestamp);
+            let elapsed = (to - from).max(0);
+            if total_weight == 0 {
+                if elapsed > 0 {
+                    reward.end_timestamp = reward
+                        .end_timestamp
+                        .max(current_timestamp)
+                        .checked_add(elapsed)
+                        .ok_or(Error::ArithmeticError)?;
+                }
+            } else {
+                let emitted = (elapsed as u128)
+                    .checked_mul(reward.emissions_per_second as u128)
+                    .and_then(|emitted| emitted.checked_add(*undistributed as u128))
+                    .ok_or(Error::ArithmeticError)?;
+                if emitted > 0 {
+                    *undistributed = 0;
+                    reward.reward_per_weight = emitted
+                        .checked_mul(REWARD_PER_WEIGHT_SCALE)
+                        .ok_or(Error::ArithmeticError)?
+                        .checked_div(total_weight as u128)
+                        .ok_or(Error::ArithmeticError)?
+                        .checked_add(reward.reward_per_weight)
+                        .ok_or(Error::ArithmeticError)?;
+                }
+            }
+            reward.last_update_timestamp = reward.last_update_timestamp.max(current_timestamp);
+        }
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct RewardInfo {
+    pub mint: Pubkey,
+    pub reward_vault: Pubkey,
+    pub emissions_per_second: u64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub last_update_timestamp: i64,
+    pub reward_per_weight: u128,
+}
+
+impl RewardInfo {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 8 + 16;
+
+    pub fn is_initialized(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+
+    /// Whether the stream ended long enough ago for its slot to be reused.
+    pub fn is_expired(&self, current_timestamp: i64) -> bool {
+        self.is_initialized()
+            && self.end_timestamp.saturating_add(REWARD_CLAIM_PERIOD) <= current_timestamp
+    }
 }
 
+/// Lock terms a vault accepts. Zero values leave the corresponding bound unset.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct LockPolicy {
+    pub min_duration: i64,
+    pub max_duration: i64,
+    /// When any preset is set, lock durations must match one of them.
+    pub duration_presets: [i64; MAX_DURATION_PRESETS],
+    pub min_deposit: u64,
+}
+
+impl LockPolicy {
+    pub const LEN: usize = 8 + 8 + 8 * MAX_DURATION_PRESETS + 8;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_duration >= 0 && self.max_duration >= 0,
+            Error::InvalidLockPolicy
+        );
+        require!(
+            self.max_duration == 0 || self.min_duration <= self.max_duration,
+            Error::InvalidLockPolicy
+        );
+        for preset in self.duration_presets.iter().filter(|preset| **preset != 0) {
+            require!(*preset > 0, Error::InvalidLockPolicy);
+            self.check_bounds(*preset)
+                .map_err(|_| error!(Error::InvalidLockPolicy))?;
+        }
+        Ok(())
+    }
+
+    /// Checks a lock running for `duration` more seconds against the policy.
+    pub fn validate_duration(&self, duration: i64) -> Result<()> {
+        self.check_bounds(duration)?;
+
+        let mut presets = self
+            .duration_presets
+            .iter()
+            .filter(|preset| **preset != 0)
+            .peekable();
+        if presets.peek().is_some() {
+            require!(
+                presets.any(|preset| duration <= *preset
+                    && duration >= preset.saturating_sub(DURATION_PRESET_TOLERANCE)),
+                Error::LockDurationNotAllowed
+            );
+        }
+        Ok(())
+    }
+
+    fn check_bounds(&self, duration: i64) -> Result<()> {
+        require!(duration >= self.min_duration, Error::LockDurationTooShort);
+        if self.max_duration > 0 {
+            require!(duration <= self.max_duration, Error::LockDurationTooLong);
+        }
+        Ok(())
+    }
+}
+
+/// New fields go at the end, so `migrate_account` can grow older accounts.
 #[account]
 pub struct UserLock {
     pub user: Pubkey,
     pub amount: u64,
     pub unlock_timestamp: i64,
+    /// Whole-token part of `deposit_basis_0`, kept for older readers.
     pub deposit_token_per_lp_0: u64,
+    /// Whole-token part of `deposit_basis_1`, kept for older readers.
     pub deposit_token_per_lp_1: u64,
+    pub reward_debts: [u128; MAX_REWARDS],
+    pub rewards_owed: [u64; MAX_REWARDS],
+    /// `amount * remaining_duration / max_duration` as of `weight_timestamp`.
+    pub weight: u64,
+    pub last_deposit_timestamp: i64,
+    /// Position NFT whose holder owns this lock; default when not tokenized.
+    pub position_mint: Pubkey,
+    /// Principal can never be withdrawn; only LP fee growth can be claimed.
+    pub is_permanent: bool,
+    /// Term the lock renews for at each expiry; zero when rollover is off.
+    pub rollover_duration: i64,
+    /// Anyone may withdraw the lock to its owner once it has unlocked.
+    pub keeper_enabled: bool,
+    /// Share of the owner's proceeds paid to the keeper that withdraws.
+    pub keeper_tip_bps: u16,
+    /// `Vault::reward_generations` the reward debts and owed rewards refer to.
+    pub reward_generations: [u32; MAX_REWARDS],
+    /// When `weight` was last computed.
+    pub weight_timestamp: i64,
+    /// Older and newer lower bounds on the amount held since their timestamps,
+    /// from which the votes at a proposal snapshot are read.
+    pub vote_checkpoints: [VoteCheckpoint; 2],
+    /// Per-LP amount of token 0 the lock's principal is valued at, scaled by
+    /// `TOKEN_PER_LP_SCALE`. Value above it is growth.
+    pub deposit_basis_0: u128,
+    /// Per-LP amount of token 1 the lock's principal is valued at, scaled by
+    /// `TOKEN_PER_LP_SCALE`.
+    pub deposit_basis_1: u128,
+    /// Owner that enabled keepers. They may only act while it still owns the lock.
+    pub keeper_authority: Pubkey,
+    /// Vault the lock belongs to, so locks can be listed per vault.
+    pub vault: Pubkey,
 }
 
 impl UserLock {
-    pub const LEN: usize = 32 + 8 + 8 + 8 + 8;
+    pub const LEN: usize = 32
+        + 8
+        + 8
+        + 8
+        + 8
+        + 16 * MAX_REWARDS
+        + 8 * MAX_REWARDS
+        + 8
+        + 8
+        + 32
+        + 1
+        + 8
+        + 1
+        + 2
+        + 4 * MAX_REWARDS
+        + 8
+        + VoteCheckpoint::LEN * 2
+        + 16
+        + 16
+        + 32
+        + 32;
+
+    pub fn is_tokenized(&self) -> bool {
+        self.position_mint != Pubkey::default()
+    }
+
+    /// Who controls this lock: the holder of its position NFT once one has been
+    /// minted, otherwise the lock's `user`.
+    pub fn owner(
+        &self,
+        position_token_account: Option<&InterfaceAccount<TokenAccount>>,
+    ) -> Result<Pubkey> {
+        if self.is_tokenized() {
+            let position_token_account = position_token_account.ok_or(Error::Unauthorized)?;
+            require!(
+                position_token_account.mint == self.position_mint
+                    && position_token_account.amount == 1,
+                Error::Unauthorized
+            );
+            Ok(position_token_account.owner)
+        } else {
+            Ok(self.user)
+        }
+    }
+
+    /// Checks that `owner` controls this lock.
+    pub fn assert_owner(
+        &self,
+        owner: &Pubkey,
+        position_token_account: Option<&InterfaceAccount<TokenAccount>>,
+    ) -> Result<()> {
+        require_keys_eq!(
+            self.owner(position_token_account)?,
+            *owner,
+            Error::Unauthorized
+        );
+        Ok(())
+    }
+
+    /// Sets the scaled per-LP basis of both pool tokens.
+    pub fn set_basis(&mut self, deposit_basis_0: u128, deposit_basis_1: u128) {
+        self.deposit_basis_0 = deposit_basis_0;
+        self.deposit_basis_1 = deposit_basis_1;
+        self.deposit_token_per_lp_0 =
+            u64::try_from(deposit_basis_0 / TOKEN_PER_LP_SCALE).unwrap_or(u64::MAX);
+        self.deposit_token_per_lp_1 =
+            u64::try_from(deposit_basis_1 / TOKEN_PER_LP_SCALE).unwrap_or(u64::MAX);
+    }
+
+    /// Records a change of `amount`, which must already be applied. Each
+    /// checkpoint only ever drops to the new amount, so LP added after a
+    /// snapshot never counts towards it. Once the newer checkpoint is older than
+    /// any open proposal's snapshot it becomes the older one, and a fresh one
+    /// starts at the current amount.
+    pub fn checkpoint_votes(&mut self, current_timestamp: i64) {
+        let [older, newer] = &mut self.vote_checkpoints;
+        if newer.timestamp <= current_timestamp.saturating_sub(PROPOSAL_VOTING_PERIOD) {
+            *older = *newer;
+            *newer = VoteCheckpoint {
+                amount: self.amount,
+                timestamp: current_timestamp,
+            };
+        }
+        older.amount = older.amount.min(self.amount);
+        newer.amount = newer.amount.min(self.amount);
+    }
+
+    /// LP held throughout from `snapshot_timestamp` until now, and so at most
+    /// what the lock held at the snapshot. Valid for snapshots of proposals
+    /// still open for voting.
+    pub fn votes_at(&self, snapshot_timestamp: i64) -> u64 {
+        if self.last_deposit_timestamp < snapshot_timestamp {
+            return self.amount;
+        }
+        self.vote_checkpoints
+            .iter()
+            .rev()
+            .find(|checkpoint| checkpoint.timestamp < snapshot_timestamp)
+            .map_or(0, |checkpoint| checkpoint.amount)
+    }
+
+    /// Checks the unlock time requested by a deposit into this lock. `owner_deposit`
+    /// is false when a third party funds it.
+    pub fn check_deposit_terms(
+        &self,
+        vault: &Vault,
+        owner_deposit: bool,
+        unlock_timestamp: i64,
+        current_timestamp: i64,
+    ) -> Result<()> {
+        require!(unlock_timestamp > current_timestamp, Error::InvalidInput);
+
+        if self.amount > 0 {
+            // Topping up may extend the lock but never shorten it.
+            require!(
+                unlock_timestamp >= self.unlock_timestamp,
+                Error::InvalidInput
+            );
+            // A third party may top up an existing lock, but must not move its unlock time.
+            // Once tokenized, the NFT holder rather than `beneficiary` owns the lock.
+            if !owner_deposit || self.is_tokenized() {
+                require!(
+                    unlock_timestamp == self.unlock_timestamp,
+                    Error::LockTermsMismatch
+                );
+            }
+        }
+
+        // Terms already accepted for an existing lock are not re-checked on a plain top-up.
+        if self.amount == 0 || unlock_timestamp != self.unlock_timestamp {
+            vault
+                .lock_policy
+                .validate_duration(unlock_timestamp - current_timestamp)?;
+        }
+        Ok(())
+    }
+
+    /// Adds `amount` of newly locked LP valued at the given scaled per-LP token
+    /// amounts. The lock's basis becomes the amount-weighted blend of what it
+    /// already held and the new LP. Checkpoints rewards and weight.
+    pub fn credit(
+        &mut self,
+        vault: &mut Vault,
+        amount: u64,
+        unlock_timestamp: i64,
+        deposit_basis_0: u128,
+        deposit_basis_1: u128,
+        current_timestamp: i64,
+    ) -> Result<()> {
+        vault.update_rewards(current_timestamp)?;
+        self.settle_rewards(vault, current_timestamp)?;
+
+        let deposit_basis_0 =
+            math::blend_basis(self.amount, self.deposit_basis_0, amount, deposit_basis_0)
+                .map_err(Error::from)?;
+        let deposit_basis_1 =
+            math::blend_basis(self.amount, self.deposit_basis_1, amount, deposit_basis_1)
+                .map_err(Error::from)?;
+        self.set_basis(deposit_basis_0, deposit_basis_1);
+        self.amount = self
+            .amount
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticError)?;
+        self.unlock_timestamp = unlock_timestamp;
+        self.last_deposit_timestamp = current_timestamp;
+        self.checkpoint_votes(current_timestamp);
+
+        vault.total_locked = vault
+            .total_locked
+            .checked_add(amount)
+            .ok_or(Error::ArithmeticError)?;
+        if self.is_permanent {
+            vault.total_permanent_locked = vault
+                .total_permanent_locked
+                .checked_add(amount)
+                .ok_or(Error::ArithmeticError)?;
+        }
+        self.refresh_weight(vault, current_timestamp)?;
+        self.reset_reward_debts(vault)
+    }
+
+    /// Moves an expired rollover lock's unlock time forward by whole terms until it
+    /// lies in the future. Returns whether the lock was rolled over.
+    pub fn roll_over(&mut self, current_timestamp: i64) -> Result<bool> {
+        if self.rollover_duration <= 0 || self.unlock_timestamp > current_timestamp {
+            return Ok(false);
+        }
+        let terms = (current_timestamp - self.unlock_timestamp) / self.rollover_duration + 1;
+        self.unlock_timestamp = terms
+            .checked_mul(self.rollover_duration)
+            .and_then(|duration| self.unlock_timestamp.checked_add(duration))
+            .ok_or(Error::ArithmeticError)?;
+        Ok(true)
+    }
+
+    /// Weight of this lock at `current_timestamp`, decaying linearly to zero at unlock.
+    pub fn current_weight(&self, vault: &Vault, current_timestamp: i64) -> Result<u64> {
+        let max_duration = vault.weight_duration;
+        let remaining = self
+            .unlock_timestamp
+            .saturating_sub(current_timestamp)
+            .clamp(0, max_duration);
+        let weight = (self.amount as u128)
+            .checked_mul(remaining as u128)
+            .ok_or(Error::ArithmeticError)?
+            / max_duration as u128;
+        Ok(weight as u64)
+    }
+
+    /// Re-computes `weight` and carries the difference into `vault.total_weight`.
+    pub fn refresh_weight(&mut self, vault: &mut Vault, current_timestamp: i64) -> Result<()> {
+        let weight = self.current_weight(vault, current_timestamp)?;
+        vault.total_weight = vault
+            .total_weight
+            .checked_sub(self.weight)
+            .ok_or(Error::ArithmeticError)?
+            .checked_add(weight)
+            .ok_or(Error::ArithmeticError)?;
+        self.weight = weight;
+        self.weight_timestamp = current_timestamp;
+        Ok(())
+    }
+
+    /// Time-averaged weight since `weight_timestamp`, which is what the lock
+    /// has actually been entitled to while its stored `weight` went stale.
+    /// Never above `weight`.
+    fn average_weight(&self, vault: &Vault, current_timestamp: i64) -> Result<u64> {
+        let (from, to) = (self.weight_timestamp as i128, current_timestamp as i128);
+        if to <= from || self.weight == 0 {
+            return Ok(self.weight);
+        }
+        // Remaining time stays capped at `max_duration` until `decay_start`,
+        // then falls linearly to zero at `unlock`.
+        let max_duration = vault.weight_duration as i128;
+        let unlock = self.unlock_timestamp as i128;
+        let decay_start = unlock - max_duration;
+        let capped = (to.min(decay_start) - from).max(0) * max_duration;
+        let (start, end) = (from.max(decay_start), to.min(unlock));
+        let decaying = if end > start {
+            ((unlock - start).pow(2) - (unlock - end).pow(2)) / 2
+        } else {
+            0
+        };
+        let average_remaining = (capped + decaying) / (to - from);
+        let weight = (self.amount as u128)
+            .checked_mul(average_remaining as u128)
+            .ok_or(Error::ArithmeticError)?
+            / max_duration as u128;
+        Ok((weight as u64).min(self.weight))
+    }
+
+    /// Moves rewards accrued since the last checkpoint into `rewards_owed`.
+    /// `vault.update_rewards` must have been called first. Whatever is still
+    /// owed from a stream whose slot has since been reused is forfeited.
+    ///
+    /// Rewards accrue at the stored `weight`, but are only paid at the weight
+    /// the lock decayed to in the meantime. The rest goes back to the vault
+    /// for the other lockers.
+    pub fn settle_rewards(&mut self, vault: &mut Vault, current_timestamp: i64) -> Result<()> {
+        let average_weight = self.average_weight(vault, current_timestamp)?;
+        for (index, reward) in vault.rewards.iter().enumerate() {
+            if self.reward_generations[index] != vault.reward_generations[index] {
+                // Last checkpointed before this stream was added, so the whole
+                // of its `reward_per_weight` accrued at the current weight.
+                self.reward_generations[index] = vault.reward_generations[index];
+                self.reward_debts[index] = 0;
+                self.rewards_owed[index] = 0;
+            }
+            if !reward.is_initialized() {
+                continue;
+            }
+            let accrued = self.accrued_reward(reward)?;
+            let pending = accrued.saturating_sub(self.reward_debts[index]);
+            let earned = if self.weight == 0 {
+                0
+            } else {
+                pending
+                    .checked_mul(average_weight as u128)
+                    .ok_or(Error::ArithmeticError)?
+                    / self.weight as u128
+            };
+            let forfeited = u64::try_from(pending - earned).map_err(|_| Error::ArithmeticError)?;
+            vault.undistributed_rewards[index] = vault.undistributed_rewards[index]
+                .checked_add(forfeited)
+                .ok_or(Error::ArithmeticError)?;
+            self.rewards_owed[index] = self.rewards_owed[index]
+                .checked_add(u64::try_from(earned).map_err(|_| Error::ArithmeticError)?)
+                .ok_or(Error::ArithmeticError)?;
+            self.reward_debts[index] = accrued;
+        }
+        Ok(())
+    }
+
+    /// Re-bases reward debts after `weight` changed.
+    pub fn reset_reward_debts(&mut self, vault: &Vault) -> Result<()> {
+        for (index, reward) in vault.rewards.iter().enumerate() {
+            self.reward_debts[index] = self.accrued_reward(reward)?;
+        }
+        self.reward_generations = vault.reward_generations;
+        Ok(())
+    }
+
+    fn accrued_reward(&self, reward: &RewardInfo) -> Result<u128> {
+        let accrued = (self.weight as u128)
+            .checked_mul(reward.reward_per_weight)
+            .ok_or(Error::ArithmeticError)?
+            / REWARD_PER_WEIGHT_SCALE;
+        Ok(accrued)
+    }
+}
+
+/// The lock held at least `amount` at every moment since `timestamp`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct VoteCheckpoint {
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+impl VoteCheckpoint {
+    pub const LEN: usize = 8 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ProposalKind {
+    FeeBps { fee_bps: u16 },
+    FeeRecipient { fee_recipient: Pubkey },
+}
+
+impl ProposalKind {
+    pub const LEN: usize = 1 + 32;
+}
+
+#[account]
+pub struct Proposal {
+    pub vault: Pubkey,
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub kind: ProposalKind,
+    /// Locks vote with what they held at this time.
+    pub snapshot_timestamp: i64,
+    pub snapshot_total_locked: u64,
+    pub voting_ends_at: i64,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub const LEN: usize = 32 + 8 + 32 + ProposalKind::LEN + 8 + 8 + 8 + 8 + 8 + 1 + 1;
+}
+
+/// Marks that `user_lock` has voted on `proposal`, whoever owns it now.
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub user_lock: Pubkey,
+    pub voter: Pubkey,
+    pub votes: u64,
+    pub support: bool,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 32 + 32 + 32 + 8 + 1;
 }
 
 #[event]
 pub struct DepositEvent {
     pub user: Pubkey,
+    pub depositor: Pubkey,
     pub vault: Pubkey,
     pub pool_state: Pubkey,
     pub amount: u64,
@@ -60,6 +899,181 @@ pub struct DepositEvent {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct LockExtendedEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub previous_unlock_timestamp: i64,
+    pub unlock_timestamp: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockWeightUpdatedEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub weight: u64,
+    pub total_weight: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardAddedEvent {
+    pub vault: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_index: u8,
+    pub emissions_per_second: u64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+}
+
+#[event]
+pub struct RewardsClaimedEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub reward_mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionMintedEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub user_lock: Pubkey,
+    pub position_mint: Pubkey,
+}
+
+#[event]
+pub struct LockTransferredEvent {
+    pub vault: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub unlock_timestamp: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockSplitEvent {
+    pub vault: Pubkey,
+    pub source_lock: Pubkey,
+    pub destination_lock: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub unlock_timestamp: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LocksMergedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub source_lock: Pubkey,
+    pub destination_lock: Pubkey,
+    pub amount: u64,
+    pub total_amount: u64,
+    pub unlock_timestamp: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RolloverUpdatedEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub rollover_duration: i64,
+    pub unlock_timestamp: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockRolledOverEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub previous_unlock_timestamp: i64,
+    pub unlock_timestamp: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct KeeperUpdatedEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub keeper_enabled: bool,
+    pub keeper_tip_bps: u16,
+}
+
+#[event]
+pub struct KeeperTipPaidEvent {
+    pub keeper: Pubkey,
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub tip_0_amount: u64,
+    pub tip_1_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LockMadePermanentEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub user_lock: Pubkey,
+    pub amount: u64,
+    pub total_permanent_locked: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LpFeesClaimedEvent {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub pool_state: Pubkey,
+    pub lp_amount: u64,
+    pub token_0_amount: u64,
+    pub token_1_amount: u64,
+    pub fee_0_amount: u64,
+    pub fee_1_amount: u64,
+    pub timestamp: i64,
+    /// Lock the LP came out of; `user` is its owner, who may not be the
+    /// key the lock is held under.
+    pub user_lock: Pubkey,
+}
+
+#[event]
+pub struct ProposalCreatedEvent {
+    pub vault: Pubkey,
+    pub proposal: Pubkey,
+    pub proposer: Pubkey,
+    pub kind: ProposalKind,
+    pub voting_ends_at: i64,
+}
+
+#[event]
+pub struct VoteCastEvent {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub votes: u64,
+    pub support: bool,
+}
+
+#[event]
+pub struct ProposalExecutedEvent {
+    pub vault: Pubkey,
+    pub proposal: Pubkey,
+    pub kind: ProposalKind,
+    pub votes_for: u64,
+    pub votes_against: u64,
+}
+
+#[event]
+pub struct BatchWithdrawResultEvent {
+    pub index: u16,
+    pub user_lock: Pubkey,
+    pub success: bool,
+    /// Program error code of a skipped position; zero on success.
+    pub error_code: u64,
+}
+
 #[event]
 pub struct WithdrawEvent {
     pub user: Pubkey,
@@ -71,6 +1085,15 @@ pub struct WithdrawEvent {
     pub fee_0_amount: u64,
     pub fee_1_amount: u64,
     pub timestamp: i64,
+    /// Lock the LP came out of; `user` is its owner, who may not be the
+    /// key the lock is held under.
+    pub user_lock: Pubkey,
+}
+
+impl From<math::MathError> for Error {
+    fn from(_: math::MathError) -> Self {
+        Error::ArithmeticError
+    }
 }
 
 #[error_code]
@@ -85,4 +1108,60 @@ pub enum Error {
     ArithmeticError,
     #[msg("Account not initialized")]
     AccountNotInitialized,
+    #[msg("Lock terms can only be changed by the lock owner")]
+    LockTermsMismatch,
+    #[msg("Pool is not on the vault allowlist")]
+    PoolNotAllowlisted,
+    #[msg("Signer is not allowed to perform this action")]
+    Unauthorized,
+    #[msg("Invalid lock policy")]
+    InvalidLockPolicy,
+    #[msg("Lock duration is shorter than the vault minimum")]
+    LockDurationTooShort,
+    #[msg("Lock duration is longer than the vault maximum")]
+    LockDurationTooLong,
+    #[msg("Lock duration does not match any of the vault presets")]
+    LockDurationNotAllowed,
+    #[msg("Deposit amount is below the vault minimum")]
+    DepositTooSmall,
+    #[msg("All reward slots of this vault are in use")]
+    RewardSlotsFull,
+    #[msg("Reward mint is already emitted by this vault")]
+    DuplicateReward,
+    #[msg("Invalid reward index")]
+    InvalidRewardIndex,
+    #[msg("Fee exceeds the maximum allowed")]
+    FeeTooHigh,
+    #[msg("Voting period has ended")]
+    VotingClosed,
+    #[msg("Voting period has not ended yet")]
+    VotingNotEnded,
+    #[msg("Lock was changed after the proposal snapshot")]
+    LockAfterSnapshot,
+    #[msg("Proposal did not pass")]
+    ProposalNotPassed,
+    #[msg("Proposal was already executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Lock already has a position NFT")]
+    AlreadyTokenized,
+    #[msg("Destination lock already holds a position")]
+    DestinationLockNotEmpty,
+    #[msg("Lock is permanent")]
+    PermanentLock,
+    #[msg("Lock is not permanent")]
+    LockNotPermanent,
+    #[msg("No LP fee growth to claim")]
+    NoFeesToClaim,
+    #[msg("Liquidity received is below the requested minimum")]
+    SlippageExceeded,
+    #[msg("Lock rolls over at expiry; opt out of rollover first")]
+    RolloverActive,
+    #[msg("Rollover opt-out is inside the notice period")]
+    RolloverNoticeTooLate,
+    #[msg("Lock is not due for rollover")]
+    RolloverNotDue,
+    #[msg("Rollover term must be longer than the notice period")]
+    RolloverTermTooShort,
+    #[msg("Locks roll over on different terms")]
+    RolloverMismatch,
 }
@@ -0,0 +1,7 @@
+pub mod pool;
+pub mod position;
+pub mod token;
+
+pub use pool::*;
+pub use position::*;
+pub use token::*;
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use raydium_cp_swap::states::PoolState;
+
+use crate::Error;
+
+/// Checks the pool's token vaults against `pool_state` and returns its
+/// reserves, excluding accrued protocol and fund fees, and its LP supply.
+pub fn pool_amounts(
+    pool_state: &PoolState,
+    token_0_vault: &InterfaceAccount<TokenAccount>,
+    token_1_vault: &InterfaceAccount<TokenAccount>,
+) -> Result<(u64, u64, u64)> {
+    require!(
+        token_0_vault.key() == pool_state.token_0_vault,
+        Error::InvalidInput
+    );
+    require!(
+        token_1_vault.key() == pool_state.token_1_vault,
+        Error::InvalidInput
+    );
+
+    let (vault_0_amount, vault_1_amount) =
+        pool_state.vault_amount_without_fee(token_0_vault.amount, token_1_vault.amount);
+    Ok((vault_0_amount, vault_1_amount, pool_state.lp_supply))
+}
@@ -0,0 +1,209 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::{self, get_associated_token_address_with_program_id},
+    token_2022::{
+        self, spl_token_2022::instruction::AuthorityType, Burn, CloseAccount, MintTo, SetAuthority,
+    },
+    token_2022_extensions::{
+        spl_pod::optional_keys::OptionalNonZeroPubkey,
+        spl_token_metadata_interface::state::TokenMetadata, token_metadata_initialize,
+        TokenMetadataInitialize,
+    },
+};
+
+use crate::{
+    Error, PositionMintedEvent, UserLock, Vault, POSITION_NAME, POSITION_SYMBOL,
+    VAULT_AUTHORITY_SEED,
+};
+
+/// Accounts that issue and retire the position NFT of a lock. The mint is the
+/// lock's `POSITION_MINT_SEED` PDA, created with the vault authority as mint,
+/// close and metadata-pointer authority and as permanent delegate, so the NFT
+/// can be burned when its lock is emptied whoever holds it.
+pub struct PositionNft<'info> {
+    pub mint: AccountInfo<'info>,
+    pub token_account: AccountInfo<'info>,
+    pub vault_authority: AccountInfo<'info>,
+    pub token_2022_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> PositionNft<'info> {
+    /// Writes the metadata into the freshly created mint, mints the single NFT
+    /// to `holder`'s associated token account, creating it if needed, and ties
+    /// `user_lock` to it.
+    pub fn issue(
+        &self,
+        vault: &Account<'info, Vault>,
+        user_lock: &mut Account<'info, UserLock>,
+        holder: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        uri: String,
+    ) -> Result<()> {
+        require!(!user_lock.is_tokenized(), Error::AlreadyTokenized);
+        require_keys_eq!(
+            self.token_account.key(),
+            get_associated_token_address_with_program_id(
+                holder.key,
+                self.mint.key,
+                self.token_2022_program.key,
+            ),
+            Error::InvalidInput
+        );
+
+        let vault_key = vault.key();
+        let seeds = &[
+            VAULT_AUTHORITY_SEED,
+            vault.pool_state.as_ref(),
+            vault_key.as_ref(),
+            &[vault.authority_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        // The metadata extension reallocates the mint, so top it up for rent first.
+        let metadata = TokenMetadata {
+            update_authority: OptionalNonZeroPubkey(self.vault_authority.key()),
+            mint: self.mint.key(),
+            name: POSITION_NAME.to_string(),
+            symbol: POSITION_SYMBOL.to_string(),
+            uri: uri.clone(),
+            ..Default::default()
+        };
+        let required_lamports =
+            Rent::get()?.minimum_balance(self.mint.data_len() + metadata.tlv_size_of()?);
+        let extra_lamports = required_lamports.saturating_sub(self.mint.lamports());
+        if extra_lamports > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    self.system_program.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: self.mint.clone(),
+                    },
+                ),
+                extra_lamports,
+            )?;
+        }
+
+        token_metadata_initialize(
+            CpiContext::new_with_signer(
+                self.token_2022_program.clone(),
+                TokenMetadataInitialize {
+                    program_id: self.token_2022_program.clone(),
+                    metadata: self.mint.clone(),
+                    update_authority: self.vault_authority.clone(),
+                    mint_authority: self.vault_authority.clone(),
+                    mint: self.mint.clone(),
+                },
+                signer_seeds,
+            ),
+            POSITION_NAME.to_string(),
+            POSITION_SYMBOL.to_string(),
+            uri,
+        )?;
+
+        if self.token_account.data_is_empty() {
+            associated_token::create(CpiContext::new(
+                self.associated_token_program.clone(),
+                associated_token::Create {
+                    payer: payer.clone(),
+                    associated_token: self.token_account.clone(),
+                    authority: holder.clone(),
+                    mint: self.mint.clone(),
+                    system_program: self.system_program.clone(),
+                    token_program: self.token_2022_program.clone(),
+                },
+            ))?;
+        }
+
+        token_2022::mint_to(
+            CpiContext::new_with_signer(
+                self.token_2022_program.clone(),
+                MintTo {
+                    mint: self.mint.clone(),
+                    to: self.token_account.clone(),
+                    authority: self.vault_authority.clone(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+
+        // Fix the supply at one.
+        token_2022::set_authority(
+            CpiContext::new_with_signer(
+                self.token_2022_program.clone(),
+                SetAuthority {
+                    current_authority: self.vault_authority.clone(),
+                    account_or_mint: self.mint.clone(),
+                },
+                signer_seeds,
+            ),
+            AuthorityType::MintTokens,
+            None,
+        )?;
+
+        user_lock.position_mint = self.mint.key();
+
+        emit!(PositionMintedEvent {
+            user: holder.key(),
+            vault: vault_key,
+            user_lock: user_lock.key(),
+            position_mint: user_lock.position_mint,
+        });
+
+        Ok(())
+    }
+
+    /// Burns the NFT of an emptied lock from `token_account`, closes its mint
+    /// into `rent_destination` and unties `user_lock` from it. Its owner may
+    /// tokenize the lock again with `mint_position_nft`.
+    pub fn retire(
+        &self,
+        vault: &Account<'info, Vault>,
+        user_lock: &mut UserLock,
+        rent_destination: &AccountInfo<'info>,
+    ) -> Result<()> {
+        require_keys_eq!(
+            self.mint.key(),
+            user_lock.position_mint,
+            Error::InvalidInput
+        );
+
+        let vault_key = vault.key();
+        let seeds = &[
+            VAULT_AUTHORITY_SEED,
+            vault.pool_state.as_ref(),
+            vault_key.as_ref(),
+            &[vault.authority_bump],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        token_2022::burn(
+            CpiContext::new_with_signer(
+                self.token_2022_program.clone(),
+                Burn {
+                    mint: self.mint.clone(),
+                    from: self.token_account.clone(),
+                    authority: self.vault_authority.clone(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+
+        token_2022::close_account(CpiContext::new_with_signer(
+            self.token_2022_program.clone(),
+            CloseAccount {
+                account: self.mint.clone(),
+                destination: rent_destination.clone(),
+                authority: self.vault_authority.clone(),
+            },
+            signer_seeds,
+        ))?;
+
+        user_lock.position_mint = Pubkey::default();
+        Ok(())
+    }
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_2022::spl_token_2022::{
+        self,
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions,
+        },
+    },
+    token_interface::Mint,
+};
+
+use crate::Error;
+
+/// Transfer fee withheld by a Token-2022 mint when `pre_fee_amount` is sent.
+/// Returns 0 for SPL Token mints and mints without the TransferFee extension.
+pub fn get_transfer_fee(mint: &InterfaceAccount<Mint>, pre_fee_amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner == Token::id() {
+        return Ok(0);
+    }
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+    let fee = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
+        transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, pre_fee_amount)
+            .ok_or(Error::ArithmeticError)?
+    } else {
+        0
+    };
+    Ok(fee)
+}
+
+/// Transfer fee that must be added to `post_fee_amount` for it to arrive in full.
+pub fn get_transfer_inverse_fee(
+    mint: &InterfaceAccount<Mint>,
+    post_fee_amount: u64,
+) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner == Token::id() {
+        return Ok(0);
+    }
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+    let fee = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
+        transfer_fee_config
+            .calculate_inverse_epoch_fee(Clock::get()?.epoch, post_fee_amount)
+            .ok_or(Error::ArithmeticError)?
+    } else {
+        0
+    };
+    Ok(fee)
+}
+
+/// Amount that arrives at the destination when `pre_fee_amount` is sent.
+pub fn amount_after_transfer_fee(
+    mint: &InterfaceAccount<Mint>,
+    pre_fee_amount: u64,
+) -> Result<u64> {
+    let fee = get_transfer_fee(mint, pre_fee_amount)?;
+    let amount = pre_fee_amount
+        .checked_sub(fee)
+        .ok_or(Error::ArithmeticError)?;
+    Ok(amount)
+}
+
+/// Amount to send for `post_fee_amount` to arrive at the destination.
+pub fn amount_before_transfer_fee(
+    mint: &InterfaceAccount<Mint>,
+    post_fee_amount: u64,
+) -> Result<u64> {
+    let fee = get_transfer_inverse_fee(mint, post_fee_amount)?;
+    let amount = post_fee_amount
+        .checked_add(fee)
+        .ok_or(Error::ArithmeticError)?;
+    Ok(amount)
+}
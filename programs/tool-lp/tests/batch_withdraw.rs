@@ -0,0 +1,81 @@
+mod common;
+
+use common::{assert_error, Fixture, TestUser, DAY, LP_SUPPLY, RESERVE_0, RESERVE_1};
+use tool_lp::Error;
+
+const DEPOSIT_AMOUNT: u64 = 100_000;
+
+/// Locks `DEPOSIT_AMOUNT` for the primary user until `now + DAY` and for a
+/// second user until `now + second_duration`, both open to keepers. Returns
+/// both users and a keeper.
+async fn two_locks(fixture: &mut Fixture, second_duration: i64) -> (TestUser, TestUser, TestUser) {
+    let now = fixture.now().await;
+    let first = fixture.primary_user();
+    let second = fixture.add_user();
+    for (user, unlock_timestamp) in [(&first, now + DAY), (&second, now + second_duration)] {
+        fixture
+            .deposit_as(user, DEPOSIT_AMOUNT, unlock_timestamp)
+            .await
+            .unwrap();
+        fixture.set_keeper_as(user, true, 0).await.unwrap();
+    }
+    fixture.warp_to(now + DAY).await;
+    let keeper = fixture.add_user();
+    (first, second, keeper)
+}
+
+#[tokio::test]
+async fn batch_withdraw_empties_every_lock() {
+    let mut fixture = Fixture::with_vault().await;
+    let (first, second, keeper) = two_locks(&mut fixture, DAY).await;
+
+    fixture
+        .batch_withdraw(&keeper, &[&first, &second], false)
+        .await
+        .unwrap();
+
+    for user in [&first, &second] {
+        let user_lock = fixture.try_user_lock_account(user).await.unwrap();
+        assert_eq!(user_lock.amount, 0);
+        assert_eq!(
+            fixture.token_amount(&user.token_0_account).await,
+            DEPOSIT_AMOUNT * RESERVE_0 / LP_SUPPLY
+        );
+        assert_eq!(
+            fixture.token_amount(&user.token_1_account).await,
+            DEPOSIT_AMOUNT * RESERVE_1 / LP_SUPPLY
+        );
+    }
+    assert_eq!(fixture.vault_account().await.total_locked, 0);
+}
+
+#[tokio::test]
+async fn batch_withdraw_skips_a_lock_that_is_not_due() {
+    let mut fixture = Fixture::with_vault().await;
+    let (first, second, keeper) = two_locks(&mut fixture, 2 * DAY).await;
+
+    fixture
+        .batch_withdraw(&keeper, &[&first, &second], true)
+        .await
+        .unwrap();
+
+    let first_lock = fixture.try_user_lock_account(&first).await.unwrap();
+    let second_lock = fixture.try_user_lock_account(&second).await.unwrap();
+    assert_eq!(first_lock.amount, 0);
+    assert_eq!(second_lock.amount, DEPOSIT_AMOUNT);
+    assert_eq!(fixture.vault_account().await.total_locked, DEPOSIT_AMOUNT);
+}
+
+#[tokio::test]
+async fn batch_withdraw_fails_on_a_lock_that_is_not_due() {
+    let mut fixture = Fixture::with_vault().await;
+    let (first, second, keeper) = two_locks(&mut fixture, 2 * DAY).await;
+
+    let result = fixture
+        .batch_withdraw(&keeper, &[&first, &second], false)
+        .await;
+
+    assert_error(result, Error::LockNotYetExpired);
+    let first_lock = fixture.try_user_lock_account(&first).await.unwrap();
+    assert_eq!(first_lock.amount, DEPOSIT_AMOUNT);
+}
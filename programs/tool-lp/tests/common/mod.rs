@@ -0,0 +1,1207 @@
+//! Offline test harness. Both tool-lp and Raydium cp-swap run natively inside
+//! `solana-program-test`; the Raydium pool, its vaults and LP mint are fixture
+//! accounts, so reserves can be moved directly to simulate growth or loss.
+
+#![allow(dead_code)]
+
+use anchor_lang::{
+    prelude::Pubkey,
+    solana_program::{
+        account_info::AccountInfo, clock::Clock, entrypoint::ProgramResult,
+        instruction::Instruction, program_pack::Pack, system_program, sysvar,
+    },
+    AccountDeserialize, AccountSerialize, AnchorDeserialize, Discriminator, InstructionData,
+    ToAccountMetas,
+};
+use anchor_spl::{
+    associated_token::{
+        self, get_associated_token_address, get_associated_token_address_with_program_id,
+    },
+    token::spl_token,
+    token_2022::{
+        self,
+        spl_token_2022::{self, extension::StateWithExtensions},
+    },
+};
+use raydium_cp_swap::states::{
+    AmmConfig, PoolState, OBSERVATION_SEED, POOL_LP_MINT_SEED, POOL_SEED, POOL_VAULT_SEED,
+};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    instruction::InstructionError,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+use tool_lp::{
+    Config, LockPolicy, PoolAllowlistEntry, UserLock, Vault, WithdrawQuote, ADMIN_WALLET,
+    CONFIG_SEED, POOL_ALLOWLIST_SEED, POSITION_MINT_SEED, USER_LOCK_SEED, VAULT_AUTHORITY_SEED,
+    VAULT_SEED, VAULT_TOKEN_SEED,
+};
+
+pub const DECIMALS: u8 = 6;
+pub const LP_SUPPLY: u64 = 1_000_000;
+pub const USER_LP_AMOUNT: u64 = 200_000;
+/// Initial reserves: 10 token 0 and 40 token 1 per LP.
+pub const RESERVE_0: u64 = 10_000_000;
+pub const RESERVE_1: u64 = 40_000_000;
+pub const DAY: i64 = 24 * 60 * 60;
+
+// Anchor's entry wants accounts that live as long as their data borrows.
+fn tool_lp_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    tool_lp::entry(program_id, accounts, data)
+}
+
+fn raydium_entry(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    raydium_cp_swap::entry(program_id, accounts, data)
+}
+
+/// A depositor with its own LP, pool token accounts and lock.
+pub struct TestUser {
+    pub keypair: Keypair,
+    pub lp_account: Pubkey,
+    pub token_0_account: Pubkey,
+    pub token_1_account: Pubkey,
+    pub user_lock: Pubkey,
+}
+
+/// A Raydium pool yet to be created by `create_pool_and_lock`, with its
+/// mints and the primary user's token accounts already set up.
+pub struct NewPool {
+    pub amm_config: Pubkey,
+    pub pool_state: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub creator_token_0: Pubkey,
+    pub creator_token_1: Pubkey,
+    pub vault: Pubkey,
+}
+
+impl NewPool {
+    pub fn lp_mint(&self) -> Pubkey {
+        self.raydium_address(&[POOL_LP_MINT_SEED.as_bytes(), self.pool_state.as_ref()])
+    }
+
+    pub fn token_0_vault(&self) -> Pubkey {
+        self.raydium_address(&[
+            POOL_VAULT_SEED.as_bytes(),
+            self.pool_state.as_ref(),
+            self.token_0_mint.as_ref(),
+        ])
+    }
+
+    pub fn token_1_vault(&self) -> Pubkey {
+        self.raydium_address(&[
+            POOL_VAULT_SEED.as_bytes(),
+            self.pool_state.as_ref(),
+            self.token_1_mint.as_ref(),
+        ])
+    }
+
+    pub fn vault_authority(&self) -> Pubkey {
+        tool_lp_address(&[
+            VAULT_AUTHORITY_SEED,
+            self.pool_state.as_ref(),
+            self.vault.as_ref(),
+        ])
+    }
+
+    pub fn vault_token_account(&self) -> Pubkey {
+        tool_lp_address(&[
+            VAULT_TOKEN_SEED,
+            self.pool_state.as_ref(),
+            self.vault.as_ref(),
+        ])
+    }
+
+    pub fn user_lock(&self, owner: &Pubkey) -> Pubkey {
+        tool_lp_address(&[USER_LOCK_SEED, self.vault.as_ref(), owner.as_ref()])
+    }
+
+    fn raydium_address(&self, seeds: &[&[u8]]) -> Pubkey {
+        Pubkey::find_program_address(seeds, &raydium_cp_swap::ID).0
+    }
+}
+
+pub struct Fixture {
+    pub context: ProgramTestContext,
+    pub user: Keypair,
+    pub pool_state: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub lp_mint: Pubkey,
+    pub raydium_authority: Pubkey,
+    pub user_lp_account: Pubkey,
+    pub user_token_0_account: Pubkey,
+    pub user_token_1_account: Pubkey,
+    pub vault: Pubkey,
+    pub vault_authority: Pubkey,
+    pub vault_token_account: Pubkey,
+    pub user_lock: Pubkey,
+}
+
+impl Fixture {
+    pub async fn new() -> Self {
+        let mut program_test = ProgramTest::default();
+        program_test.prefer_bpf(false);
+        program_test.add_program("tool_lp", tool_lp::ID, processor!(tool_lp_entry));
+        program_test.add_program(
+            "raydium_cp_swap",
+            raydium_cp_swap::ID,
+            processor!(raydium_entry),
+        );
+
+        let user = Keypair::new();
+        let pool_state = Pubkey::new_unique();
+        let token_0_mint = Pubkey::new_unique();
+        let token_1_mint = Pubkey::new_unique();
+        let token_0_vault = Pubkey::new_unique();
+        let token_1_vault = Pubkey::new_unique();
+        let lp_mint = Pubkey::new_unique();
+        let user_lp_account = Pubkey::new_unique();
+        let user_token_0_account = Pubkey::new_unique();
+        let user_token_1_account = Pubkey::new_unique();
+        let (raydium_authority, auth_bump) = Pubkey::find_program_address(
+            &[raydium_cp_swap::AUTH_SEED.as_bytes()],
+            &raydium_cp_swap::ID,
+        );
+
+        program_test.add_account(
+            user.pubkey(),
+            Account::new(10_000_000_000, 0, &system_program::ID),
+        );
+
+        // `initialize_config` is admin-only, so the config is a fixture.
+        let (config, config_bump) = Pubkey::find_program_address(&[CONFIG_SEED], &tool_lp::ID);
+        let mut config_data = Vec::new();
+        Config {
+            admin: ADMIN_WALLET,
+            permissionless_vaults: true,
+            bump: config_bump,
+        }
+        .try_serialize(&mut config_data)
+        .unwrap();
+        program_test.add_account(config, program_account(config_data, tool_lp::ID));
+
+        let mut pool: PoolState = bytemuck::Zeroable::zeroed();
+        pool.token_0_vault = token_0_vault;
+        pool.token_1_vault = token_1_vault;
+        pool.lp_mint = lp_mint;
+        pool.token_0_mint = token_0_mint;
+        pool.token_1_mint = token_1_mint;
+        pool.token_0_program = spl_token::ID;
+        pool.token_1_program = spl_token::ID;
+        pool.auth_bump = auth_bump;
+        pool.lp_mint_decimals = DECIMALS;
+        pool.mint_0_decimals = DECIMALS;
+        pool.mint_1_decimals = DECIMALS;
+        pool.lp_supply = LP_SUPPLY;
+        let mut pool_data = PoolState::DISCRIMINATOR.to_vec();
+        pool_data.extend_from_slice(bytemuck::bytes_of(&pool));
+        program_test.add_account(pool_state, program_account(pool_data, raydium_cp_swap::ID));
+
+        for (mint, authority, supply) in [
+            (token_0_mint, user.pubkey(), RESERVE_0),
+            (token_1_mint, user.pubkey(), RESERVE_1),
+            (lp_mint, raydium_authority, LP_SUPPLY),
+        ] {
+            program_test.add_account(mint, mint_account(&authority, supply));
+        }
+        for (address, mint, owner, amount) in [
+            (token_0_vault, token_0_mint, raydium_authority, RESERVE_0),
+            (token_1_vault, token_1_mint, raydium_authority, RESERVE_1),
+            (user_lp_account, lp_mint, user.pubkey(), USER_LP_AMOUNT),
+            (user_token_0_account, token_0_mint, user.pubkey(), 0),
+            (user_token_1_account, token_1_mint, user.pubkey(), 0),
+        ] {
+            program_test.add_account(address, token_account(&mint, &owner, amount));
+        }
+
+        let (vault, _) =
+            Pubkey::find_program_address(&[VAULT_SEED, pool_state.as_ref()], &tool_lp::ID);
+        let (vault_authority, _) = Pubkey::find_program_address(
+            &[VAULT_AUTHORITY_SEED, pool_state.as_ref(), vault.as_ref()],
+            &tool_lp::ID,
+        );
+        let (vault_token_account, _) = Pubkey::find_program_address(
+            &[VAULT_TOKEN_SEED, pool_state.as_ref(), vault.as_ref()],
+            &tool_lp::ID,
+        );
+        let (user_lock, _) = Pubkey::find_program_address(
+            &[USER_LOCK_SEED, vault.as_ref(), user.pubkey().as_ref()],
+            &tool_lp::ID,
+        );
+
+        Self {
+            context: program_test.start_with_context().await,
+            user,
+            pool_state,
+            token_0_mint,
+            token_1_mint,
+            token_0_vault,
+            token_1_vault,
+            lp_mint,
+            raydium_authority,
+            user_lp_account,
+            user_token_0_account,
+            user_token_1_account,
+            vault,
+            vault_authority,
+            vault_token_account,
+            user_lock,
+        }
+    }
+
+    /// A fixture with the vault already initialized.
+    pub async fn with_vault() -> Self {
+        let mut fixture = Self::new().await;
+        fixture.initialize_vault().await.unwrap();
+        fixture
+    }
+
+    /// The fixture's own user, which also pays for setup transactions.
+    pub fn primary_user(&self) -> TestUser {
+        TestUser {
+            keypair: self.user.insecure_clone(),
+            lp_account: self.user_lp_account,
+            token_0_account: self.user_token_0_account,
+            token_1_account: self.user_token_1_account,
+            user_lock: self.user_lock,
+        }
+    }
+
+    /// Funds another user with `USER_LP_AMOUNT` of LP and empty pool token
+    /// accounts. The LP comes out of the fixture supply not held by anyone.
+    pub fn add_user(&mut self) -> TestUser {
+        let keypair = Keypair::new();
+        let (user_lock, _) = Pubkey::find_program_address(
+            &[
+                USER_LOCK_SEED,
+                self.vault.as_ref(),
+                keypair.pubkey().as_ref(),
+            ],
+            &tool_lp::ID,
+        );
+        let user = TestUser {
+            lp_account: Pubkey::new_unique(),
+            token_0_account: Pubkey::new_unique(),
+            token_1_account: Pubkey::new_unique(),
+            user_lock,
+            keypair,
+        };
+
+        let owner = user.keypair.pubkey();
+        self.context.set_account(
+            &owner,
+            &Account::new(10_000_000_000, 0, &system_program::ID).into(),
+        );
+        for (address, mint, amount) in [
+            (user.lp_account, self.lp_mint, USER_LP_AMOUNT),
+            (user.token_0_account, self.token_0_mint, 0),
+            (user.token_1_account, self.token_1_mint, 0),
+        ] {
+            self.context
+                .set_account(&address, &token_account(&mint, &owner, amount).into());
+        }
+        user
+    }
+
+    pub async fn process(&mut self, instruction: Instruction) -> Result<(), BanksClientError> {
+        let user = self.user.insecure_clone();
+        self.process_as(&user, instruction).await
+    }
+
+    pub async fn process_as(
+        &mut self,
+        signer: &Keypair,
+        instruction: Instruction,
+    ) -> Result<(), BanksClientError> {
+        let blockhash = self.context.get_new_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&signer.pubkey()),
+            &[signer],
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub async fn initialize_vault(&mut self) -> Result<(), BanksClientError> {
+        let accounts = tool_lp::accounts::InitializeVault {
+            vault: self.vault,
+            initializer: self.user.pubkey(),
+            token_0_vault: self.token_0_vault,
+            token_1_vault: self.token_1_vault,
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+            pool_state: self.pool_state,
+            token_mint: self.lp_mint,
+            vault_token_account: self.vault_token_account,
+            vault_token_0_account: self.vault_token_0_account(),
+            vault_token_1_account: self.vault_token_1_account(),
+            vault_authority: self.vault_authority,
+            system_program: system_program::ID,
+            token_program: spl_token::ID,
+            token_0_program: spl_token::ID,
+            token_1_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            config: Pubkey::find_program_address(&[CONFIG_SEED], &tool_lp::ID).0,
+            pool_allowlist: None,
+        };
+        self.process(Instruction {
+            program_id: tool_lp::ID,
+            accounts: accounts.to_account_metas(None),
+            data: tool_lp::instruction::InitializeVault.data(),
+        })
+        .await
+    }
+
+    /// Sets up the mints and accounts for a second pool, funding the primary
+    /// user with `amount_0` and `amount_1` of its tokens.
+    pub fn new_pool(&mut self, amount_0: u64, amount_1: u64) -> NewPool {
+        let creator = self.user.pubkey();
+        // Raydium orders a pool's mints by address.
+        let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (token_0_mint, token_1_mint) = (mint_a.min(mint_b), mint_a.max(mint_b));
+        // An all-zero config charges no fees, including the pool creation fee.
+        let amm_config = Pubkey::new_unique();
+        let mut amm_config_data = AmmConfig::DISCRIMINATOR.to_vec();
+        amm_config_data.resize(AmmConfig::LEN, 0);
+        self.context.set_account(
+            &amm_config,
+            &program_account(amm_config_data, raydium_cp_swap::ID).into(),
+        );
+        self.context.set_account(
+            &raydium_cp_swap::create_pool_fee_reveiver::ID,
+            &token_account(&spl_token::native_mint::ID, &ADMIN_WALLET, 0).into(),
+        );
+
+        let pool_state = Pubkey::find_program_address(
+            &[
+                POOL_SEED.as_bytes(),
+                amm_config.as_ref(),
+                token_0_mint.as_ref(),
+                token_1_mint.as_ref(),
+            ],
+            &raydium_cp_swap::ID,
+        )
+        .0;
+        let pool = NewPool {
+            amm_config,
+            pool_state,
+            token_0_mint,
+            token_1_mint,
+            creator_token_0: Pubkey::new_unique(),
+            creator_token_1: Pubkey::new_unique(),
+            vault: tool_lp_address(&[VAULT_SEED, pool_state.as_ref()]),
+        };
+        for (mint, account, amount) in [
+            (token_0_mint, pool.creator_token_0, amount_0),
+            (token_1_mint, pool.creator_token_1, amount_1),
+        ] {
+            self.context
+                .set_account(&mint, &mint_account(&creator, amount).into());
+            self.context
+                .set_account(&account, &token_account(&mint, &creator, amount).into());
+        }
+        pool
+    }
+
+    /// Creates `pool` with all of the primary user's tokens and locks the LP.
+    pub async fn create_pool_and_lock(
+        &mut self,
+        pool: &NewPool,
+        unlock_timestamp: i64,
+        pool_allowlist: Option<Pubkey>,
+    ) -> Result<(), BanksClientError> {
+        let creator = self.user.pubkey();
+        let init_amount_0 = self.token_amount(&pool.creator_token_0).await;
+        let init_amount_1 = self.token_amount(&pool.creator_token_1).await;
+        let vault_authority = pool.vault_authority();
+        let lp_mint = pool.lp_mint();
+        let accounts = tool_lp::accounts::CreatePoolAndLock {
+            creator,
+            config: tool_lp_address(&[CONFIG_SEED]),
+            pool_allowlist,
+            vault: pool.vault,
+            user_lock: pool.user_lock(&creator),
+            vault_token_account: pool.vault_token_account(),
+            vault_token_0_account: get_associated_token_address(
+                &vault_authority,
+                &pool.token_0_mint,
+            ),
+            vault_token_1_account: get_associated_token_address(
+                &vault_authority,
+                &pool.token_1_mint,
+            ),
+            vault_authority,
+            cp_swap_program: raydium_cp_swap::ID,
+            amm_config: pool.amm_config,
+            authority: self.raydium_authority,
+            pool_state: pool.pool_state,
+            token_0_mint: pool.token_0_mint,
+            token_1_mint: pool.token_1_mint,
+            lp_mint,
+            creator_token_0: pool.creator_token_0,
+            creator_token_1: pool.creator_token_1,
+            creator_lp_token: get_associated_token_address(&creator, &lp_mint),
+            token_0_vault: pool.token_0_vault(),
+            token_1_vault: pool.token_1_vault(),
+            create_pool_fee: raydium_cp_swap::create_pool_fee_reveiver::ID,
+            observation_state: Pubkey::find_program_address(
+                &[OBSERVATION_SEED.as_bytes(), pool.pool_state.as_ref()],
+                &raydium_cp_swap::ID,
+            )
+            .0,
+            token_program: spl_token::ID,
+            token_0_program: spl_token::ID,
+            token_1_program: spl_token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        };
+        self.process(Instruction {
+            program_id: tool_lp::ID,
+            accounts: accounts.to_account_metas(None),
+            data: tool_lp::instruction::CreatePoolAndLock {
+                init_amount_0,
+                init_amount_1,
+                open_time: 0,
+                lock_policy: LockPolicy::default(),
+                unlock_timestamp,
+            }
+            .data(),
+        })
+        .await
+    }
+
+    /// Overwrites the config fixture, as the admin would with `update_config`.
+    pub fn set_permissionless_vaults(&mut self, permissionless_vaults: bool) {
+        let (config, bump) = Pubkey::find_program_address(&[CONFIG_SEED], &tool_lp::ID);
+        let mut data = Vec::new();
+        Config {
+            admin: ADMIN_WALLET,
+            permissionless_vaults,
+            bump,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        self.context
+            .set_account(&config, &program_account(data, tool_lp::ID).into());
+    }
+
+    /// Allowlists `pool_state` as the admin would, returning the entry.
+    pub fn allowlist_pool(&mut self, pool_state: &Pubkey) -> Pubkey {
+        let (entry, bump) =
+            Pubkey::find_program_address(&[POOL_ALLOWLIST_SEED, pool_state.as_ref()], &tool_lp::ID);
+        let mut data = Vec::new();
+        PoolAllowlistEntry {
+            pool_state: *pool_state,
+            bump,
+        }
+        .try_serialize(&mut data)
+        .unwrap();
+        self.context
+            .set_account(&entry, &program_account(data, tool_lp::ID).into());
+        entry
+    }
+
+    pub async fn deposit(
+        &mut self,
+        amount: u64,
+        unlock_timestamp: i64,
+    ) -> Result<(), BanksClientError> {
+        let user = self.primary_user();
+        self.deposit_as(&user, amount, unlock_timestamp).await
+    }
+
+    pub async fn deposit_as(
+        &mut self,
+        user: &TestUser,
+        amount: u64,
+        unlock_timestamp: i64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self
+            .deposit_instruction(user, amount, unlock_timestamp)
+            .await;
+        self.process_as(&user.keypair, instruction).await
+    }
+
+    /// A deposit into `user`'s own lock.
+    pub async fn deposit_instruction(
+        &mut self,
+        user: &TestUser,
+        amount: u64,
+        unlock_timestamp: i64,
+    ) -> Instruction {
+        let owner = user.keypair.pubkey();
+        let position_token_account = self.held_position_account(user).await;
+        let accounts = tool_lp::accounts::Deposit {
+            vault: self.vault,
+            user: owner,
+            beneficiary: owner,
+            user_lock: user.user_lock,
+            user_token_account: user.lp_account,
+            vault_token_account: self.vault_token_account,
+            token_mint: self.lp_mint,
+            pool_state: self.pool_state,
+            token_0_vault: self.token_0_vault,
+            token_1_vault: self.token_1_vault,
+            token_program: spl_token::ID,
+            clock: sysvar::clock::ID,
+            system_program: system_program::ID,
+            position_token_account,
+            payer: None,
+        };
+        Instruction {
+            program_id: tool_lp::ID,
+            accounts: accounts.to_account_metas(None),
+            data: tool_lp::instruction::Deposit {
+                amount,
+                unlock_timestamp,
+            }
+            .data(),
+        }
+    }
+
+    /// Adds liquidity from the primary user's pool token accounts and locks
+    /// the LP it mints.
+    pub async fn deposit_liquidity_and_lock(
+        &mut self,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64,
+        minimum_lp_amount: u64,
+        unlock_timestamp: i64,
+    ) -> Result<(), BanksClientError> {
+        let user = self.primary_user();
+        let accounts = tool_lp::accounts::DepositLiquidityAndLock {
+            vault: self.vault,
+            user: user.keypair.pubkey(),
+            user_lock: user.user_lock,
+            vault_token_account: self.vault_token_account,
+            vault_token_0_account: self.vault_token_0_account(),
+            vault_token_1_account: self.vault_token_1_account(),
+            user_token_0_account: user.token_0_account,
+            user_token_1_account: user.token_1_account,
+            token_0_vault: self.token_0_vault,
+            token_1_vault: self.token_1_vault,
+            vault_authority: self.vault_authority,
+            pool_state: self.pool_state,
+            cp_swap_program: raydium_cp_swap::ID,
+            authority: self.raydium_authority,
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+            lp_mint: self.lp_mint,
+            token_0_program: spl_token::ID,
+            token_1_program: spl_token::ID,
+            token_program: spl_token::ID,
+            token_program_2022: token_2022::ID,
+            system_program: system_program::ID,
+            position_token_account: self.held_position_account(&user).await,
+        };
+        self.process_as(
+            &user.keypair,
+            Instruction {
+                program_id: tool_lp::ID,
+                accounts: accounts.to_account_metas(None),
+                data: tool_lp::instruction::DepositLiquidityAndLock {
+                    maximum_token_0_amount,
+                    maximum_token_1_amount,
+                    minimum_lp_amount,
+                    unlock_timestamp,
+                }
+                .data(),
+            },
+        )
+        .await
+    }
+
+    pub async fn withdraw(&mut self, lp_token_amount: u64) -> Result<(), BanksClientError> {
+        let user = self.primary_user();
+        self.withdraw_as(&user, lp_token_amount).await
+    }
+
+    pub async fn withdraw_as(
+        &mut self,
+        user: &TestUser,
+        lp_token_amount: u64,
+    ) -> Result<(), BanksClientError> {
+        self.withdraw_by(user, user, lp_token_amount).await
+    }
+
+    /// Withdraws `user`'s lock to `user`, signed by `signer`, who is a keeper
+    /// unless it is `user` itself.
+    pub async fn withdraw_by(
+        &mut self,
+        signer: &TestUser,
+        user: &TestUser,
+        lp_token_amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let instruction = self
+            .withdraw_instruction(signer, user, lp_token_amount)
+            .await;
+        self.process_as(&signer.keypair, instruction).await
+    }
+
+    pub async fn withdraw_instruction(
+        &mut self,
+        signer: &TestUser,
+        user: &TestUser,
+        lp_token_amount: u64,
+    ) -> Instruction {
+        let owner = user.keypair.pubkey();
+        let position_mint = self
+            .try_user_lock_account(user)
+            .await
+            .filter(|user_lock| user_lock.is_tokenized())
+            .map(|user_lock| user_lock.position_mint);
+        let accounts = tool_lp::accounts::Withdraw {
+            vault: self.vault,
+            user: signer.keypair.pubkey(),
+            user_lock: user.user_lock,
+            position_token_account: position_mint
+                .map(|position_mint| position_token_account(&owner, &position_mint)),
+            position_mint,
+            vault_token_account: self.vault_token_account,
+            vault_token_0_account: self.vault_token_0_account(),
+            vault_token_1_account: self.vault_token_1_account(),
+            user_token_0_account: user.token_0_account,
+            user_token_1_account: user.token_1_account,
+            keeper_token_0_account: None,
+            keeper_token_1_account: None,
+            fee_recipient: ADMIN_WALLET,
+            fee_recipient_token_0_account: self.fee_token_0_account(),
+            fee_recipient_token_1_account: self.fee_token_1_account(),
+            token_0_vault: self.token_0_vault,
+            token_1_vault: self.token_1_vault,
+            vault_authority: self.vault_authority,
+            pool_state: self.pool_state,
+            cp_swap_program: raydium_cp_swap::ID,
+            authority: self.raydium_authority,
+            token_mint: self.lp_mint,
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+            lp_mint: self.lp_mint,
+            token_0_program: spl_token::ID,
+            token_1_program: spl_token::ID,
+            token_program: spl_token::ID,
+            token_program_2022: token_2022::ID,
+            memo_program: spl_memo::id(),
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            clock: sysvar::clock::ID,
+            payer: None,
+        };
+        Instruction {
+            program_id: tool_lp::ID,
+            accounts: accounts.to_account_metas(None),
+            data: tool_lp::instruction::Withdraw { lp_token_amount }.data(),
+        }
+    }
+
+    /// Withdraws each of `users`' locks in full in one `batch_withdraw`,
+    /// signed by `signer`.
+    pub async fn batch_withdraw(
+        &mut self,
+        signer: &TestUser,
+        users: &[&TestUser],
+        skip_failed: bool,
+    ) -> Result<(), BanksClientError> {
+        // `Withdraw` accounts that depend on the lock, by position.
+        const LOCK_ACCOUNTS: [usize; 5] = [2, 3, 4, 8, 9];
+        let mut accounts = tool_lp::accounts::BatchWithdraw {
+            user: signer.keypair.pubkey(),
+        }
+        .to_account_metas(None);
+        for (index, user) in users.iter().enumerate() {
+            let withdraw_accounts = self.withdraw_instruction(signer, user, 0).await.accounts;
+            if index == 0 {
+                accounts.extend(
+                    withdraw_accounts
+                        .iter()
+                        .enumerate()
+                        .filter(|(position, _)| !LOCK_ACCOUNTS.contains(position))
+                        .map(|(_, account)| account.clone()),
+                );
+            }
+            accounts.extend(
+                LOCK_ACCOUNTS
+                    .iter()
+                    .map(|position| withdraw_accounts[*position].clone()),
+            );
+        }
+        self.process_as(
+            &signer.keypair,
+            Instruction {
+                program_id: tool_lp::ID,
+                accounts,
+                data: tool_lp::instruction::BatchWithdraw { skip_failed }.data(),
+            },
+        )
+        .await
+    }
+
+    /// Simulates `quote_withdraw` for the primary user's lock and decodes the
+    /// quote from its return data.
+    pub async fn quote_withdraw(&mut self, lp_token_amount: u64) -> WithdrawQuote {
+        let accounts = tool_lp::accounts::QuoteWithdraw {
+            vault: self.vault,
+            user_lock: self.user_lock,
+            pool_state: self.pool_state,
+            token_0_vault: self.token_0_vault,
+            token_1_vault: self.token_1_vault,
+            vault_0_mint: self.token_0_mint,
+            vault_1_mint: self.token_1_mint,
+        };
+        let instruction = Instruction {
+            program_id: tool_lp::ID,
+            accounts: accounts.to_account_metas(None),
+            data: tool_lp::instruction::QuoteWithdraw { lp_token_amount }.data(),
+        };
+        let blockhash = self.context.get_new_latest_blockhash().await.unwrap();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&self.user.pubkey()),
+            &[&self.user],
+            blockhash,
+        );
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+        WithdrawQuote::try_from_slice(&return_data.data).unwrap()
+    }
+
+    pub async fn make_lock_permanent(&mut self, user: &TestUser) -> Result<(), BanksClientError> {
+        let accounts = tool_lp::accounts::MakeLockPermanent {
+            vault: self.vault,
+            user: user.keypair.pubkey(),
+            user_lock: user.user_lock,
+            position_token_account: self.held_position_account(user).await,
+        };
+        self.process_as(
+            &user.keypair,
+            Instruction {
+                program_id: tool_lp::ID,
+                accounts: accounts.to_account_metas(None),
+                data: tool_lp::instruction::MakeLockPermanent.data(),
+            },
+        )
+        .await
+    }
+
+    /// Claims the fee growth of `user`'s permanent lock to `user`.
+    pub async fn claim_lp_fees(&mut self, user: &TestUser) -> Result<(), BanksClientError> {
+        let mut instruction = self.withdraw_instruction(user, user, 0).await;
+        instruction.data = tool_lp::instruction::ClaimLpFees.data();
+        self.process_as(&user.keypair, instruction).await
+    }
+
+    pub async fn set_rollover(&mut self, rollover_duration: i64) -> Result<(), BanksClientError> {
+        let user = self.primary_user();
+        let accounts = tool_lp::accounts::SetRollover {
+            vault: self.vault,
+            user: user.keypair.pubkey(),
+            user_lock: user.user_lock,
+            position_token_account: self.held_position_account(&user).await,
+        };
+        self.process(Instruction {
+            program_id: tool_lp::ID,
+            accounts: accounts.to_account_metas(None),
+            data: tool_lp::instruction::SetRollover { rollover_duration }.data(),
+        })
+        .await
+    }
+
+    pub async fn set_keeper_as(
+        &mut self,
+        user: &TestUser,
+        keeper_enabled: bool,
+        keeper_tip_bps: u16,
+    ) -> Result<(), BanksClientError> {
+        let accounts = tool_lp::accounts::SetKeeper {
+            vault: self.vault,
+            user: user.keypair.pubkey(),
+            user_lock: user.user_lock,
+            position_token_account: self.held_position_account(user).await,
+        };
+        self.process_as(
+            &user.keypair,
+            Instruction {
+                program_id: tool_lp::ID,
+                accounts: accounts.to_account_metas(None),
+                data: tool_lp::instruction::SetKeeper {
+                    keeper_enabled,
+                    keeper_tip_bps,
+                }
+                .data(),
+            },
+        )
+        .await
+    }
+
+    /// Tokenizes the lock of `user`, who receives its position NFT.
+    pub async fn mint_position_nft(&mut self, user: &TestUser) -> Result<(), BanksClientError> {
+        let owner = user.keypair.pubkey();
+        let position_mint = position_mint(&user.user_lock);
+        let accounts = tool_lp::accounts::MintPositionNft {
+            vault: self.vault,
+            owner,
+            payer: owner,
+            user_lock: user.user_lock,
+            position_mint,
+            position_token_account: position_token_account(&owner, &position_mint),
+            vault_authority: self.vault_authority,
+            token_2022_program: token_2022::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+        };
+        self.process_as(
+            &user.keypair,
+            Instruction {
+                program_id: tool_lp::ID,
+                accounts: accounts.to_account_metas(None),
+                data: tool_lp::instruction::MintPositionNft { uri: String::new() }.data(),
+            },
+        )
+        .await
+    }
+
+    /// Moves the lock of `from` to `to`'s lock address.
+    pub async fn transfer_lock(
+        &mut self,
+        from: &TestUser,
+        to: &TestUser,
+    ) -> Result<(), BanksClientError> {
+        let owner = from.keypair.pubkey();
+        let accounts = tool_lp::accounts::TransferLock {
+            vault: self.vault,
+            user: owner,
+            new_owner: to.keypair.pubkey(),
+            payer: owner,
+            user_lock: from.user_lock,
+            new_user_lock: to.user_lock,
+            system_program: system_program::ID,
+        };
+        self.process_as(
+            &from.keypair,
+            Instruction {
+                program_id: tool_lp::ID,
+                accounts: accounts.to_account_metas(None),
+                data: tool_lp::instruction::TransferLock.data(),
+            },
+        )
+        .await
+    }
+
+    /// Splits `amount` off the lock at `owner.user_lock`, signed by `owner`,
+    /// into `recipient`'s lock.
+    pub async fn split_lock(
+        &mut self,
+        owner: &TestUser,
+        recipient: &TestUser,
+        amount: u64,
+    ) -> Result<(), BanksClientError> {
+        let signer = owner.keypair.pubkey();
+        let accounts = tool_lp::accounts::SplitLock {
+            vault: self.vault,
+            owner: signer,
+            recipient: recipient.keypair.pubkey(),
+            payer: signer,
+            user_lock: owner.user_lock,
+            position_token_account: self.held_position_account(owner).await,
+            new_user_lock: recipient.user_lock,
+            system_program: system_program::ID,
+        };
+        self.process_as(
+            &owner.keypair,
+            Instruction {
+                program_id: tool_lp::ID,
+                accounts: accounts.to_account_metas(None),
+                data: tool_lp::instruction::SplitLock { amount }.data(),
+            },
+        )
+        .await
+    }
+
+    /// Merges the lock at `source.user_lock` into the one at
+    /// `destination.user_lock`, signed by `source`, who must own both.
+    pub async fn merge_locks(
+        &mut self,
+        source: &TestUser,
+        destination: &TestUser,
+    ) -> Result<(), BanksClientError> {
+        let source_position_mint = self
+            .try_user_lock_account(source)
+            .await
+            .filter(|user_lock| user_lock.is_tokenized())
+            .map(|user_lock| user_lock.position_mint);
+        let accounts = tool_lp::accounts::MergeLocks {
+            vault: self.vault,
+            owner: source.keypair.pubkey(),
+            source_lock: source.user_lock,
+            destination_lock: destination.user_lock,
+            source_position_token_account: self.held_position_account(source).await,
+            source_position_mint,
+            destination_position_token_account: self.held_position_account(destination).await,
+            vault_authority: self.vault_authority,
+            token_2022_program: source_position_mint.map(|_| token_2022::ID),
+        };
+        self.process_as(
+            &source.keypair,
+            Instruction {
+                program_id: tool_lp::ID,
+                accounts: accounts.to_account_metas(None),
+                data: tool_lp::instruction::MergeLocks.data(),
+            },
+        )
+        .await
+    }
+
+    /// Moves `from`'s position NFT to `to`, creating `to`'s account for it.
+    pub async fn transfer_position(
+        &mut self,
+        from: &TestUser,
+        to: &TestUser,
+    ) -> Result<(), BanksClientError> {
+        let position_mint = position_mint(&from.user_lock);
+        let from_owner = from.keypair.pubkey();
+        let to_owner = to.keypair.pubkey();
+        let instructions = [
+            associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &from_owner,
+                &to_owner,
+                &position_mint,
+                &token_2022::ID,
+            ),
+            spl_token_2022::instruction::transfer_checked(
+                &token_2022::ID,
+                &position_token_account(&from_owner, &position_mint),
+                &position_mint,
+                &position_token_account(&to_owner, &position_mint),
+                &from_owner,
+                &[],
+                1,
+                0,
+            )
+            .unwrap(),
+        ];
+        let blockhash = self.context.get_new_latest_blockhash().await?;
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&from_owner),
+            &[&from.keypair],
+            blockhash,
+        );
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    pub fn vault_token_0_account(&self) -> Pubkey {
+        get_associated_token_address(&self.vault_authority, &self.token_0_mint)
+    }
+
+    pub fn vault_token_1_account(&self) -> Pubkey {
+        get_associated_token_address(&self.vault_authority, &self.token_1_mint)
+    }
+
+    pub fn fee_token_0_account(&self) -> Pubkey {
+        get_associated_token_address(&ADMIN_WALLET, &self.token_0_mint)
+    }
+
+    pub fn fee_token_1_account(&self) -> Pubkey {
+        get_associated_token_address(&ADMIN_WALLET, &self.token_1_mint)
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        self.clock().await.unix_timestamp
+    }
+
+    pub async fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = unix_timestamp;
+        self.context.set_sysvar(&clock);
+    }
+
+    async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar().await.unwrap()
+    }
+
+    /// Overwrites a pool reserve, as if the pool had gained or lost value.
+    pub async fn set_reserves(&mut self, reserve_0: u64, reserve_1: u64) {
+        let (token_0_vault, token_1_vault) = (self.token_0_vault, self.token_1_vault);
+        self.set_token_amount(&token_0_vault, reserve_0).await;
+        self.set_token_amount(&token_1_vault, reserve_1).await;
+    }
+
+    /// Overwrites the balance of an SPL Token account.
+    pub async fn set_token_amount(&mut self, address: &Pubkey, amount: u64) {
+        let mut account = self.account(address).await.unwrap();
+        let mut token_account = spl_token::state::Account::unpack(&account.data).unwrap();
+        token_account.amount = amount;
+        spl_token::state::Account::pack(token_account, &mut account.data).unwrap();
+        self.context
+            .set_account(address, &AccountSharedData::from(account));
+    }
+
+    /// Balance of an SPL Token or Token-2022 account; zero when it does not exist.
+    pub async fn token_amount(&mut self, address: &Pubkey) -> u64 {
+        match self.account(address).await {
+            Some(account) => {
+                StateWithExtensions::<spl_token_2022::state::Account>::unpack(&account.data)
+                    .unwrap()
+                    .base
+                    .amount
+            }
+            None => 0,
+        }
+    }
+
+    pub async fn account_exists(&mut self, address: &Pubkey) -> bool {
+        self.account(address).await.is_some()
+    }
+
+    /// Cuts an account down to `len` bytes, as if created under an older layout.
+    pub async fn truncate_account(&mut self, address: &Pubkey, len: usize) {
+        let mut account = self.account(address).await.unwrap();
+        account.data.truncate(len);
+        self.context
+            .set_account(address, &AccountSharedData::from(account));
+    }
+
+    pub async fn migrate_account(&mut self, address: &Pubkey) -> Result<(), BanksClientError> {
+        let accounts = tool_lp::accounts::MigrateAccount {
+            account: *address,
+            payer: self.user.pubkey(),
+            system_program: system_program::ID,
+            vault: Some(self.vault),
+        };
+        self.process(Instruction {
+            program_id: tool_lp::ID,
+            accounts: accounts.to_account_metas(None),
+            data: tool_lp::instruction::MigrateAccount.data(),
+        })
+        .await
+    }
+
+    pub async fn account_len(&mut self, address: &Pubkey) -> usize {
+        self.account(address).await.unwrap().data.len()
+    }
+
+    pub async fn vault_account(&mut self) -> Vault {
+        let address = self.vault;
+        self.anchor_account(&address).await
+    }
+
+    pub async fn user_lock_account(&mut self) -> UserLock {
+        let address = self.user_lock;
+        self.anchor_account(&address).await
+    }
+
+    /// `user`'s account for the position NFT of their lock, if it is tokenized.
+    pub async fn held_position_account(&mut self, user: &TestUser) -> Option<Pubkey> {
+        let owner = user.keypair.pubkey();
+        self.try_user_lock_account(user)
+            .await
+            .filter(|user_lock| user_lock.is_tokenized())
+            .map(|user_lock| position_token_account(&owner, &user_lock.position_mint))
+    }
+
+    /// A user's lock, or `None` before their first deposit.
+    pub async fn try_user_lock_account(&mut self, user: &TestUser) -> Option<UserLock> {
+        let account = self.account(&user.user_lock).await?;
+        Some(UserLock::try_deserialize(&mut account.data.as_slice()).unwrap())
+    }
+
+    pub async fn pool_state_account(&mut self) -> PoolState {
+        let address = self.pool_state;
+        let account = self.account(&address).await.unwrap();
+        bytemuck::pod_read_unaligned(&account.data[PoolState::DISCRIMINATOR.len()..])
+    }
+
+    pub async fn anchor_account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let account = self.account(address).await.unwrap();
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+    }
+}
+
+fn tool_lp_address(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &tool_lp::ID).0
+}
+
+/// Mint of the position NFT of `user_lock`.
+pub fn position_mint(user_lock: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[POSITION_MINT_SEED, user_lock.as_ref()], &tool_lp::ID).0
+}
+
+/// `owner`'s account for a position NFT.
+pub fn position_token_account(owner: &Pubkey, position_mint: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, position_mint, &token_2022::ID)
+}
+
+/// Asserts that the first instruction failed with the given program error,
+/// either a `tool_lp::Error` or one of Anchor's own error codes.
+pub fn assert_error(result: Result<(), BanksClientError>, error: impl Into<u32>) {
+    match result.unwrap_err().unwrap() {
+        TransactionError::InstructionError(0, InstructionError::Custom(code)) => {
+            assert_eq!(code, error.into())
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+fn program_account(data: Vec<u8>, owner: Pubkey) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn mint_account(authority: &Pubkey, supply: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Mint::LEN];
+    spl_token::state::Mint {
+        mint_authority: Some(*authority).into(),
+        supply,
+        decimals: DECIMALS,
+        is_initialized: true,
+        freeze_authority: None.into(),
+    }
+    .pack_into_slice(&mut data);
+    program_account(data, spl_token::ID)
+}
+
+fn token_account(mint: &Pubkey, owner: &Pubkey, amount: u64) -> Account {
+    let mut data = vec![0; spl_token::state::Account::LEN];
+    spl_token::state::Account {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        ..Default::default()
+    }
+    .pack_into_slice(&mut data);
+    program_account(data, spl_token::ID)
+}
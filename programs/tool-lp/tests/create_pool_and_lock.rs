@@ -0,0 +1,112 @@
+mod common;
+
+use common::{assert_error, Fixture, NewPool, DAY};
+use solana_sdk::{account::Account, signature::Signer, system_program};
+use tool_lp::{math, Error, UserLock, Vault};
+
+const INIT_AMOUNT_0: u64 = 10_000_000;
+const INIT_AMOUNT_1: u64 = 40_000_000;
+/// Raydium mints the geometric mean of the initial amounts, less the 100 LP
+/// it keeps locked in the pool.
+const CREATOR_LP_AMOUNT: u64 = 20_000_000 - 100;
+
+async fn creator_lock(fixture: &mut Fixture, pool: &NewPool) -> UserLock {
+    let address = pool.user_lock(&fixture.user.pubkey());
+    fixture.anchor_account(&address).await
+}
+
+#[tokio::test]
+async fn create_pool_and_lock_locks_the_initial_lp() {
+    let mut fixture = Fixture::new().await;
+    let pool = fixture.new_pool(INIT_AMOUNT_0, INIT_AMOUNT_1);
+    let unlock_timestamp = fixture.now().await + DAY;
+
+    fixture
+        .create_pool_and_lock(&pool, unlock_timestamp, None)
+        .await
+        .unwrap();
+
+    let vault: Vault = fixture.anchor_account(&pool.vault).await;
+    assert_eq!(vault.pool_state, pool.pool_state);
+    assert_eq!(vault.token_mint, pool.lp_mint());
+    assert_eq!(vault.total_locked, CREATOR_LP_AMOUNT);
+    assert_eq!(
+        fixture.token_amount(&pool.vault_token_account()).await,
+        CREATOR_LP_AMOUNT
+    );
+
+    // The basis is taken over the LP mint supply, all of which is locked.
+    let user_lock = creator_lock(&mut fixture, &pool).await;
+    assert_eq!(user_lock.amount, CREATOR_LP_AMOUNT);
+    assert_eq!(user_lock.unlock_timestamp, unlock_timestamp);
+    assert_eq!(
+        user_lock.deposit_basis_0,
+        math::token_per_lp(INIT_AMOUNT_0, CREATOR_LP_AMOUNT).unwrap()
+    );
+    assert_eq!(
+        user_lock.deposit_basis_1,
+        math::token_per_lp(INIT_AMOUNT_1, CREATOR_LP_AMOUNT).unwrap()
+    );
+    assert_eq!(fixture.token_amount(&pool.creator_token_0).await, 0);
+    assert_eq!(fixture.token_amount(&pool.creator_token_1).await, 0);
+}
+
+#[tokio::test]
+async fn pre_funded_vault_token_account_is_created_anyway() {
+    let mut fixture = Fixture::new().await;
+    let pool = fixture.new_pool(INIT_AMOUNT_0, INIT_AMOUNT_1);
+    let unlock_timestamp = fixture.now().await + DAY;
+    // Anyone can send lamports to the predictable address first.
+    fixture.context.set_account(
+        &pool.vault_token_account(),
+        &Account::new(1_000, 0, &system_program::ID).into(),
+    );
+
+    fixture
+        .create_pool_and_lock(&pool, unlock_timestamp, None)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        fixture.token_amount(&pool.vault_token_account()).await,
+        CREATOR_LP_AMOUNT
+    );
+    assert_eq!(
+        creator_lock(&mut fixture, &pool).await.amount,
+        CREATOR_LP_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn create_pool_and_lock_requires_an_allowlisted_pool_when_gated() {
+    let mut fixture = Fixture::new().await;
+    fixture.set_permissionless_vaults(false);
+    let pool = fixture.new_pool(INIT_AMOUNT_0, INIT_AMOUNT_1);
+    let unlock_timestamp = fixture.now().await + DAY;
+
+    let result = fixture
+        .create_pool_and_lock(&pool, unlock_timestamp, None)
+        .await;
+
+    assert_error(result, Error::PoolNotAllowlisted);
+    assert!(!fixture.account_exists(&pool.vault).await);
+}
+
+#[tokio::test]
+async fn create_pool_and_lock_accepts_an_allowlisted_pool_when_gated() {
+    let mut fixture = Fixture::new().await;
+    fixture.set_permissionless_vaults(false);
+    let pool = fixture.new_pool(INIT_AMOUNT_0, INIT_AMOUNT_1);
+    let entry = fixture.allowlist_pool(&pool.pool_state);
+    let unlock_timestamp = fixture.now().await + DAY;
+
+    fixture
+        .create_pool_and_lock(&pool, unlock_timestamp, Some(entry))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        creator_lock(&mut fixture, &pool).await.amount,
+        CREATOR_LP_AMOUNT
+    );
+}
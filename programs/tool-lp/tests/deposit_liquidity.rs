@@ -0,0 +1,78 @@
+mod common;
+
+use common::{assert_error, Fixture, DAY, RESERVE_0, RESERVE_1};
+use tool_lp::{Error, TOKEN_PER_LP_SCALE};
+
+const TOKEN_0_AMOUNT: u64 = 1_000_000;
+const TOKEN_1_AMOUNT: u64 = 5_000_000;
+/// LP that `TOKEN_0_AMOUNT` buys at the initial reserves.
+const LP_AMOUNT: u64 = 100_000;
+
+/// A fixture whose primary user holds `TOKEN_0_AMOUNT` and `TOKEN_1_AMOUNT`.
+async fn funded() -> Fixture {
+    let mut fixture = Fixture::with_vault().await;
+    let (user_0, user_1) = (fixture.user_token_0_account, fixture.user_token_1_account);
+    fixture.set_token_amount(&user_0, TOKEN_0_AMOUNT).await;
+    fixture.set_token_amount(&user_1, TOKEN_1_AMOUNT).await;
+    fixture
+}
+
+#[tokio::test]
+async fn deposit_liquidity_takes_only_what_the_lp_costs() {
+    let mut fixture = funded().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+
+    fixture
+        .deposit_liquidity_and_lock(TOKEN_0_AMOUNT, TOKEN_1_AMOUNT, LP_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+
+    // Token 0 limits the LP; the token 1 surplus never leaves the user.
+    let (user_0, user_1) = (fixture.user_token_0_account, fixture.user_token_1_account);
+    assert_eq!(fixture.token_amount(&user_0).await, 0);
+    assert_eq!(
+        fixture.token_amount(&user_1).await,
+        TOKEN_1_AMOUNT - 4 * TOKEN_0_AMOUNT
+    );
+    let (staged_0, staged_1) = (
+        fixture.vault_token_0_account(),
+        fixture.vault_token_1_account(),
+    );
+    assert_eq!(fixture.token_amount(&staged_0).await, 0);
+    assert_eq!(fixture.token_amount(&staged_1).await, 0);
+    let (token_0_vault, token_1_vault) = (fixture.token_0_vault, fixture.token_1_vault);
+    assert_eq!(
+        fixture.token_amount(&token_0_vault).await,
+        RESERVE_0 + TOKEN_0_AMOUNT
+    );
+    assert_eq!(
+        fixture.token_amount(&token_1_vault).await,
+        RESERVE_1 + 4 * TOKEN_0_AMOUNT
+    );
+
+    let user_lock = fixture.user_lock_account().await;
+    assert_eq!(user_lock.amount, LP_AMOUNT);
+    assert_eq!(user_lock.unlock_timestamp, unlock_timestamp);
+    assert_eq!(user_lock.deposit_basis_0, 10 * TOKEN_PER_LP_SCALE);
+    assert_eq!(user_lock.deposit_basis_1, 40 * TOKEN_PER_LP_SCALE);
+    assert_eq!(fixture.vault_account().await.total_locked, LP_AMOUNT);
+}
+
+#[tokio::test]
+async fn deposit_liquidity_below_the_minimum_lp_is_rejected() {
+    let mut fixture = funded().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+
+    let result = fixture
+        .deposit_liquidity_and_lock(
+            TOKEN_0_AMOUNT,
+            TOKEN_1_AMOUNT,
+            LP_AMOUNT + 1,
+            unlock_timestamp,
+        )
+        .await;
+
+    assert_error(result, Error::SlippageExceeded);
+    let user_0 = fixture.user_token_0_account;
+    assert_eq!(fixture.token_amount(&user_0).await, TOKEN_0_AMOUNT);
+}
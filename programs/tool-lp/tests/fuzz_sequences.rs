@@ -0,0 +1,290 @@
+//! Random deposit/withdraw sequences across several users, with the clock and
+//! pool reserves moving in between. A step may be rejected with a program
+//! error, but must not fail any other way, and a withdrawal the lock and pool
+//! can cover must go through. The vault invariants must hold throughout. Runs
+//! 32 cases unless `PROPTEST_CASES` is set.
+
+mod common;
+
+use common::{Fixture, TestUser, DAY, LP_SUPPLY, RESERVE_1, USER_LP_AMOUNT};
+use proptest::{prelude::*, test_runner::TestCaseError};
+use solana_program_test::BanksClientError;
+use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+use tool_lp::{math, BPS_DENOMINATOR};
+
+const USERS: usize = 3;
+
+#[derive(Clone, Debug)]
+enum LpAmount {
+    /// Basis points of the user's current lock.
+    Share(u64),
+    Exact(u64),
+}
+
+#[derive(Clone, Debug)]
+enum Op {
+    Deposit {
+        user: usize,
+        amount: u64,
+        lock_duration: i64,
+    },
+    Withdraw {
+        user: usize,
+        amount: LpAmount,
+    },
+    Warp {
+        seconds: i64,
+    },
+    SetReserves {
+        reserve_0: u64,
+        reserve_1: u64,
+    },
+}
+
+/// Reserves from dust, where the per-LP basis truncates to zero, up to values
+/// where `lp * reserve` no longer fits in a `u64`.
+fn reserve() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        0..=LP_SUPPLY,
+        LP_SUPPLY..=RESERVE_1 * 4,
+        u64::MAX / 4..=u64::MAX,
+    ]
+}
+
+fn deposit_amount() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        Just(0),
+        1..=3u64,
+        1..=USER_LP_AMOUNT,
+        Just(USER_LP_AMOUNT + 1),
+    ]
+}
+
+fn withdraw_amount() -> impl Strategy<Value = LpAmount> {
+    prop_oneof![
+        3 => (0..=BPS_DENOMINATOR).prop_map(LpAmount::Share),
+        1 => (0..=3u64).prop_map(LpAmount::Exact),
+        1 => Just(LpAmount::Exact(u64::MAX)),
+    ]
+}
+
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        3 => (0..USERS, deposit_amount(), -DAY..=30 * DAY).prop_map(
+            |(user, amount, lock_duration)| Op::Deposit {
+                user,
+                amount,
+                lock_duration,
+            }
+        ),
+        3 => (0..USERS, withdraw_amount())
+            .prop_map(|(user, amount)| Op::Withdraw { user, amount }),
+        2 => (0..=40 * DAY).prop_map(|seconds| Op::Warp { seconds }),
+        2 => (reserve(), reserve()).prop_map(|(reserve_0, reserve_1)| Op::SetReserves {
+            reserve_0,
+            reserve_1
+        }),
+    ]
+}
+
+/// Accepts a step rejected by tool-lp, Anchor, Raydium or a token program.
+/// Those all surface as custom program errors; panics, an exhausted compute
+/// budget and other runtime failures fail the case.
+fn expect_rejection(err: BanksClientError) -> Result<(), TestCaseError> {
+    match err {
+        BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(_),
+        )) => Ok(()),
+        other => Err(TestCaseError::fail(format!("unexpected error: {other:?}"))),
+    }
+}
+
+/// Balances a withdrawal is checked against.
+struct Snapshot {
+    reserve_0: u64,
+    reserve_1: u64,
+    lp_supply: u64,
+    user_0: u64,
+    user_1: u64,
+    fee_0: u64,
+    fee_1: u64,
+}
+
+impl Snapshot {
+    async fn take(fixture: &mut Fixture, user: &TestUser) -> Self {
+        let (token_0_vault, token_1_vault) = (fixture.token_0_vault, fixture.token_1_vault);
+        let (fee_0, fee_1) = (fixture.fee_token_0_account(), fixture.fee_token_1_account());
+        Self {
+            reserve_0: fixture.token_amount(&token_0_vault).await,
+            reserve_1: fixture.token_amount(&token_1_vault).await,
+            lp_supply: fixture.pool_state_account().await.lp_supply,
+            user_0: fixture.token_amount(&user.token_0_account).await,
+            user_1: fixture.token_amount(&user.token_1_account).await,
+            fee_0: fixture.token_amount(&fee_0).await,
+            fee_1: fixture.token_amount(&fee_1).await,
+        }
+    }
+}
+
+/// Withdraws from `user`'s lock and, if it succeeds, checks the payout and fee
+/// against the user's pro-rata share of the pool before the withdrawal. It must
+/// succeed when the lock has unlocked, holds the amount, and the pool pays at
+/// least one of each token per LP without overflowing the receiving accounts.
+async fn withdraw(
+    fixture: &mut Fixture,
+    user: &TestUser,
+    amount: LpAmount,
+) -> Result<(), TestCaseError> {
+    let lock = fixture.try_user_lock_account(user).await;
+    let locked = lock.as_ref().map_or(0, |lock| lock.amount);
+    let lp_amount = match amount {
+        LpAmount::Share(bps) => (locked as u128 * bps as u128 / BPS_DENOMINATOR as u128) as u64,
+        LpAmount::Exact(amount) => amount,
+    };
+    let before = Snapshot::take(fixture, user).await;
+    let now = fixture.now().await;
+    let unlocked = lock
+        .as_ref()
+        .is_some_and(|lock| now >= lock.unlock_timestamp);
+    let sane_reserves = [before.reserve_0, before.reserve_1]
+        .iter()
+        .all(|reserve| (before.lp_supply..=RESERVE_1 * 4).contains(reserve));
+    let fits = [
+        (before.user_0, before.reserve_0),
+        (before.fee_0, before.reserve_0),
+        (before.user_1, before.reserve_1),
+        (before.fee_1, before.reserve_1),
+    ]
+    .iter()
+    .all(|(balance, reserve)| balance.checked_add(*reserve).is_some());
+    let must_succeed = unlocked && lp_amount > 0 && lp_amount <= locked && sane_reserves && fits;
+    if let Err(err) = fixture.withdraw_as(user, lp_amount).await {
+        prop_assert!(
+            !must_succeed,
+            "withdrawing {} of {} unlocked LP failed: {:?}",
+            lp_amount,
+            locked,
+            err
+        );
+        return expect_rejection(err);
+    }
+    let after = Snapshot::take(fixture, user).await;
+
+    let lock = lock.expect("withdrew without a lock");
+    prop_assert!(lp_amount <= lock.amount);
+    let remaining = fixture
+        .try_user_lock_account(user)
+        .await
+        .map_or(0, |lock| lock.amount);
+    prop_assert_eq!(remaining, lock.amount - lp_amount);
+
+    let fee_bps = fixture.vault_account().await.fee_bps;
+    for (reserve, basis, received, fee) in [
+        (
+            before.reserve_0,
+            lock.deposit_basis_0,
+            after.user_0 - before.user_0,
+            after.fee_0 - before.fee_0,
+        ),
+        (
+            before.reserve_1,
+            lock.deposit_basis_1,
+            after.user_1 - before.user_1,
+            after.fee_1 - before.fee_1,
+        ),
+    ] {
+        let (lp_amount, fee) = (lp_amount as u128, fee as u128);
+        let pro_rata = lp_amount * reserve as u128 / before.lp_supply as u128;
+        prop_assert!(
+            received as u128 + fee <= pro_rata,
+            "paid {} + {} out of a {} share",
+            received,
+            fee,
+            pro_rata
+        );
+
+        let growth = pro_rata.saturating_sub(math::basis_to_tokens(lp_amount as u64, basis));
+        prop_assert!(fee <= growth, "fee {} on growth {}", fee, growth);
+        prop_assert!(
+            fee * BPS_DENOMINATOR as u128 <= growth * fee_bps as u128,
+            "fee {} above {} bps of growth {}",
+            fee,
+            fee_bps,
+            growth
+        );
+    }
+    Ok(())
+}
+
+async fn run(ops: Vec<Op>) -> Result<(), TestCaseError> {
+    let mut fixture = Fixture::with_vault().await;
+    let mut users = vec![fixture.primary_user()];
+    users.extend((1..USERS).map(|_| fixture.add_user()));
+
+    for op in ops {
+        match op {
+            Op::Deposit {
+                user,
+                amount,
+                lock_duration,
+            } => {
+                let unlock_timestamp = fixture.now().await + lock_duration;
+                if let Err(err) = fixture
+                    .deposit_as(&users[user], amount, unlock_timestamp)
+                    .await
+                {
+                    expect_rejection(err)?;
+                }
+            }
+            Op::Withdraw { user, amount } => withdraw(&mut fixture, &users[user], amount).await?,
+            Op::Warp { seconds } => {
+                let now = fixture.now().await;
+                fixture.warp_to(now + seconds).await;
+            }
+            Op::SetReserves {
+                reserve_0,
+                reserve_1,
+            } => fixture.set_reserves(reserve_0, reserve_1).await,
+        }
+
+        let mut locked = 0;
+        for user in &users {
+            if let Some(lock) = fixture.try_user_lock_account(user).await {
+                locked += lock.amount;
+            }
+        }
+        let total_locked = fixture.vault_account().await.total_locked;
+        prop_assert_eq!(total_locked, locked);
+
+        let vault_token_account = fixture.vault_token_account;
+        let vault_balance = fixture.token_amount(&vault_token_account).await;
+        prop_assert!(
+            vault_balance >= total_locked,
+            "vault holds {} of {} locked",
+            vault_balance,
+            total_locked
+        );
+    }
+    Ok(())
+}
+
+fn config() -> ProptestConfig {
+    let config = ProptestConfig::default();
+    if std::env::var_os("PROPTEST_CASES").is_some() {
+        return config;
+    }
+    ProptestConfig {
+        cases: 32,
+        ..config
+    }
+}
+
+proptest! {
+    #![proptest_config(config())]
+
+    #[test]
+    fn deposit_withdraw_sequences(ops in prop::collection::vec(op(), 1..40)) {
+        tokio::runtime::Runtime::new().unwrap().block_on(run(ops))?;
+    }
+}
@@ -0,0 +1,274 @@
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::{assert_error, Fixture, DAY, RESERVE_0, RESERVE_1, USER_LP_AMOUNT};
+use tool_lp::{Error, DEFAULT_FEE_BPS, ROLLOVER_NOTICE_PERIOD, TOKEN_PER_LP_SCALE};
+
+const DEPOSIT_AMOUNT: u64 = 100_000;
+
+/// A fixture with `DEPOSIT_AMOUNT` locked for a day, returning the unlock time.
+async fn locked() -> (Fixture, i64) {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    (fixture, unlock_timestamp)
+}
+
+#[tokio::test]
+async fn initialize_vault() {
+    let mut fixture = Fixture::new().await;
+    fixture.initialize_vault().await.unwrap();
+
+    let vault = fixture.vault_account().await;
+    assert_eq!(vault.pool_state, fixture.pool_state);
+    assert_eq!(vault.token_mint, fixture.lp_mint);
+    assert_eq!(vault.vault_token_account, fixture.vault_token_account);
+    assert_eq!(vault.fee_bps, DEFAULT_FEE_BPS);
+    assert_eq!(vault.total_locked, 0);
+
+    let vault_token_0_account = fixture.vault_token_0_account();
+    assert_eq!(fixture.token_amount(&vault_token_0_account).await, 0);
+}
+
+#[tokio::test]
+async fn deposit_locks_lp_at_the_pool_price() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+
+    let user_lock = fixture.user_lock_account().await;
+    assert_eq!(user_lock.amount, DEPOSIT_AMOUNT);
+    assert_eq!(user_lock.unlock_timestamp, unlock_timestamp);
+    assert_eq!(user_lock.deposit_basis_0, 10 * TOKEN_PER_LP_SCALE);
+    assert_eq!(user_lock.deposit_basis_1, 40 * TOKEN_PER_LP_SCALE);
+    assert_eq!(user_lock.deposit_token_per_lp_0, 10);
+    assert_eq!(fixture.vault_account().await.total_locked, DEPOSIT_AMOUNT);
+
+    let vault_token_account = fixture.vault_token_account;
+    assert_eq!(
+        fixture.token_amount(&vault_token_account).await,
+        DEPOSIT_AMOUNT
+    );
+    let user_lp_account = fixture.user_lp_account;
+    assert_eq!(
+        fixture.token_amount(&user_lp_account).await,
+        USER_LP_AMOUNT - DEPOSIT_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn top_up_adds_to_the_lock() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+
+    fixture
+        .deposit(DEPOSIT_AMOUNT / 2, unlock_timestamp + DAY)
+        .await
+        .unwrap();
+
+    let user_lock = fixture.user_lock_account().await;
+    assert_eq!(user_lock.amount, DEPOSIT_AMOUNT * 3 / 2);
+    assert_eq!(user_lock.unlock_timestamp, unlock_timestamp + DAY);
+    assert_eq!(
+        fixture.vault_account().await.total_locked,
+        DEPOSIT_AMOUNT * 3 / 2
+    );
+}
+
+#[tokio::test]
+async fn top_up_cannot_shorten_the_lock() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+
+    let result = fixture.deposit(1, unlock_timestamp - 1).await;
+
+    assert_error(result, Error::InvalidInput);
+}
+
+#[tokio::test]
+async fn top_up_may_keep_the_unlock_time() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+
+    fixture.deposit(1, unlock_timestamp).await.unwrap();
+
+    let user_lock = fixture.user_lock_account().await;
+    assert_eq!(user_lock.amount, DEPOSIT_AMOUNT + 1);
+    assert_eq!(user_lock.unlock_timestamp, unlock_timestamp);
+}
+
+#[tokio::test]
+async fn top_up_blends_the_basis_by_amount() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+    // Token 0 now trades at 40 per LP instead of 10; token 1 stays at 40.
+    fixture.set_reserves(RESERVE_0 * 4, RESERVE_1).await;
+
+    fixture
+        .deposit(DEPOSIT_AMOUNT / 2, unlock_timestamp)
+        .await
+        .unwrap();
+
+    // (100_000 * 10 + 50_000 * 40) / 150_000 = 20 per LP.
+    let user_lock = fixture.user_lock_account().await;
+    assert_eq!(user_lock.deposit_basis_0, 20 * TOKEN_PER_LP_SCALE);
+    assert_eq!(user_lock.deposit_basis_1, 40 * TOKEN_PER_LP_SCALE);
+    assert_eq!(user_lock.deposit_token_per_lp_0, 20);
+}
+
+#[tokio::test]
+async fn deposit_into_another_lp_account_is_rejected() {
+    let mut fixture = Fixture::with_vault().await;
+    let user = fixture.primary_user();
+    let unlock_timestamp = fixture.now().await + DAY;
+    let mut instruction = fixture
+        .deposit_instruction(&user, DEPOSIT_AMOUNT, unlock_timestamp)
+        .await;
+    for meta in &mut instruction.accounts {
+        if meta.pubkey == fixture.vault_token_account {
+            meta.pubkey = user.lp_account;
+        }
+    }
+
+    let result = fixture.process(instruction).await;
+
+    assert_error(result, ErrorCode::ConstraintAddress);
+    assert!(fixture.try_user_lock_account(&user).await.is_none());
+    let user_lp_account = fixture.user_lp_account;
+    assert_eq!(fixture.token_amount(&user_lp_account).await, USER_LP_AMOUNT);
+}
+
+#[tokio::test]
+async fn withdraw_before_unlock_is_rejected() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+    fixture.warp_to(unlock_timestamp - 1).await;
+
+    let result = fixture.withdraw(DEPOSIT_AMOUNT).await;
+
+    assert_error(result, Error::LockNotYetExpired);
+    assert_eq!(fixture.user_lock_account().await.amount, DEPOSIT_AMOUNT);
+}
+
+#[tokio::test]
+async fn withdraw_with_growth_pays_the_fee_on_growth() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+    let (reserve_0, reserve_1) = (RESERVE_0 * 3 / 2, RESERVE_1 * 2);
+    fixture.set_reserves(reserve_0, reserve_1).await;
+    fixture.warp_to(unlock_timestamp).await;
+
+    fixture.withdraw(DEPOSIT_AMOUNT).await.unwrap();
+
+    // Token 0: 1_500_000 redeemed against a 1_000_000 deposit, so 20% of
+    // 500_000 growth. Token 1: 8_000_000 against 4_000_000.
+    let (user_0, user_1) = (fixture.user_token_0_account, fixture.user_token_1_account);
+    let (fee_0, fee_1) = (fixture.fee_token_0_account(), fixture.fee_token_1_account());
+    assert_eq!(fixture.token_amount(&user_0).await, 1_400_000);
+    assert_eq!(fixture.token_amount(&user_1).await, 7_200_000);
+    assert_eq!(fixture.token_amount(&fee_0).await, 100_000);
+    assert_eq!(fixture.token_amount(&fee_1).await, 800_000);
+
+    assert_eq!(fixture.user_lock_account().await.amount, 0);
+    assert_eq!(fixture.vault_account().await.total_locked, 0);
+}
+
+#[tokio::test]
+async fn withdraw_with_loss_charges_no_fee() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+    let (reserve_0, reserve_1) = (RESERVE_0 / 2, RESERVE_1 * 3 / 4);
+    fixture.set_reserves(reserve_0, reserve_1).await;
+    fixture.warp_to(unlock_timestamp).await;
+
+    fixture.withdraw(DEPOSIT_AMOUNT).await.unwrap();
+
+    let (user_0, user_1) = (fixture.user_token_0_account, fixture.user_token_1_account);
+    let (fee_0, fee_1) = (fixture.fee_token_0_account(), fixture.fee_token_1_account());
+    assert_eq!(fixture.token_amount(&user_0).await, 500_000);
+    assert_eq!(fixture.token_amount(&user_1).await, 3_000_000);
+    assert_eq!(fixture.token_amount(&fee_0).await, 0);
+    assert_eq!(fixture.token_amount(&fee_1).await, 0);
+}
+
+#[tokio::test]
+async fn withdraw_at_a_fractional_price_charges_only_growth() {
+    let mut fixture = Fixture::with_vault().await;
+    // 12.345678 token 0 and 33.333333 token 1 per LP.
+    fixture.set_reserves(12_345_678, 33_333_333).await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let user_lock = fixture.user_lock_account().await;
+    assert_eq!(user_lock.deposit_basis_0, 12_345_678_000_000);
+    assert_eq!(user_lock.deposit_basis_1, 33_333_333_000_000);
+    // Token 0 doubles; token 1 stays put.
+    fixture.set_reserves(24_691_356, 33_333_333).await;
+    fixture.warp_to(unlock_timestamp).await;
+
+    fixture.withdraw(DEPOSIT_AMOUNT).await.unwrap();
+
+    // Token 0: 2_469_135 redeemed against a 1_234_568 deposit, rounded up, so
+    // 20% of 1_234_567 growth. Token 1 redeems 3_333_333 against 3_333_334 and
+    // pays nothing, where a whole-token basis of 33 would have seen growth.
+    let (user_0, user_1) = (fixture.user_token_0_account, fixture.user_token_1_account);
+    let (fee_0, fee_1) = (fixture.fee_token_0_account(), fixture.fee_token_1_account());
+    assert_eq!(fixture.token_amount(&user_0).await, 2_222_222);
+    assert_eq!(fixture.token_amount(&fee_0).await, 246_913);
+    assert_eq!(fixture.token_amount(&user_1).await, 3_333_333);
+    assert_eq!(fixture.token_amount(&fee_1).await, 0);
+}
+
+#[tokio::test]
+async fn withdraw_to_another_fee_account_is_rejected() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+    fixture.set_reserves(RESERVE_0 * 2, RESERVE_1 * 2).await;
+    fixture.warp_to(unlock_timestamp).await;
+    let user = fixture.primary_user();
+    let mut instruction = fixture
+        .withdraw_instruction(&user, &user, DEPOSIT_AMOUNT)
+        .await;
+    // The withdrawer's own token 0 account in place of the fee recipient's.
+    let fee_0 = fixture.fee_token_0_account();
+    for meta in &mut instruction.accounts {
+        if meta.pubkey == fee_0 {
+            meta.pubkey = user.token_0_account;
+        }
+    }
+
+    let result = fixture.process(instruction).await;
+
+    assert_error(result, ErrorCode::ConstraintTokenOwner);
+    assert_eq!(fixture.user_lock_account().await.amount, DEPOSIT_AMOUNT);
+    assert_eq!(fixture.token_amount(&user.token_0_account).await, 0);
+}
+
+#[tokio::test]
+async fn partial_withdraw_leaves_the_rest_locked() {
+    let (mut fixture, unlock_timestamp) = locked().await;
+    fixture.set_reserves(RESERVE_0 * 2, RESERVE_1).await;
+    fixture.warp_to(unlock_timestamp).await;
+
+    fixture.withdraw(DEPOSIT_AMOUNT / 4).await.unwrap();
+
+    // Only token 0 grew: 500_000 redeemed against a 250_000 deposit.
+    let (fee_0, fee_1) = (fixture.fee_token_0_account(), fixture.fee_token_1_account());
+    assert_eq!(fixture.token_amount(&fee_0).await, 50_000);
+    assert_eq!(fixture.token_amount(&fee_1).await, 0);
+
+    assert_eq!(
+        fixture.user_lock_account().await.amount,
+        DEPOSIT_AMOUNT * 3 / 4
+    );
+    assert_eq!(
+        fixture.vault_account().await.total_locked,
+        DEPOSIT_AMOUNT * 3 / 4
+    );
+}
+
+#[tokio::test]
+async fn rollover_term_within_the_notice_period_is_rejected() {
+    let (mut fixture, _) = locked().await;
+
+    // The lock could never be opted out in time.
+    let result = fixture.set_rollover(ROLLOVER_NOTICE_PERIOD).await;
+
+    assert_error(result, Error::RolloverTermTooShort);
+    assert_eq!(fixture.user_lock_account().await.rollover_duration, 0);
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{assert_error, Fixture, DAY};
+use tool_lp::{UserLock, Vault, ADMIN_WALLET, DEFAULT_FEE_BPS, TOKEN_PER_LP_SCALE};
+
+/// `Vault` and `UserLock` sizes before any fields were added to them.
+const ORIGINAL_VAULT_LEN: usize = 8 + 32 + 32 + 32 + 8 + 1;
+const ORIGINAL_USER_LOCK_LEN: usize = 8 + 32 + 8 + 8 + 8 + 8;
+
+#[tokio::test]
+async fn migrate_grows_an_original_user_lock() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture.deposit(1_000, unlock_timestamp).await.unwrap();
+    let before = fixture.user_lock_account().await;
+    let user_lock = fixture.user_lock;
+    fixture
+        .truncate_account(&user_lock, ORIGINAL_USER_LOCK_LEN)
+        .await;
+
+    fixture.migrate_account(&user_lock).await.unwrap();
+
+    assert_eq!(fixture.account_len(&user_lock).await, 8 + UserLock::LEN);
+    let after = fixture.user_lock_account().await;
+    assert_eq!(after.user, before.user);
+    assert_eq!(after.vault, fixture.vault);
+    assert_eq!(after.amount, 1_000);
+    assert_eq!(after.unlock_timestamp, unlock_timestamp);
+    assert_eq!(after.deposit_token_per_lp_0, before.deposit_token_per_lp_0);
+    assert_eq!(after.deposit_basis_0, 10 * TOKEN_PER_LP_SCALE);
+    assert_eq!(after.deposit_basis_1, 40 * TOKEN_PER_LP_SCALE);
+    assert_eq!(after.weight, 0);
+    assert!(!after.is_tokenized());
+    assert_eq!(after.votes_at(fixture.now().await), 1_000);
+}
+
+#[tokio::test]
+async fn migrate_grows_an_original_vault_with_defaults() {
+    let mut fixture = Fixture::with_vault().await;
+    let before = fixture.vault_account().await;
+    let vault = fixture.vault;
+    fixture.truncate_account(&vault, ORIGINAL_VAULT_LEN).await;
+
+    fixture.migrate_account(&vault).await.unwrap();
+
+    assert_eq!(fixture.account_len(&vault).await, 8 + Vault::LEN);
+    let after = fixture.vault_account().await;
+    assert_eq!(after.pool_state, before.pool_state);
+    assert_eq!(after.vault_token_account, before.vault_token_account);
+    assert_eq!(after.authority_bump, before.authority_bump);
+    assert_eq!(after.fee_bps, DEFAULT_FEE_BPS);
+    assert_eq!(after.fee_recipient, ADMIN_WALLET);
+
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture.deposit(1_000, unlock_timestamp).await.unwrap();
+}
+
+#[tokio::test]
+async fn migrate_leaves_current_accounts_alone() {
+    let mut fixture = Fixture::with_vault().await;
+    let vault = fixture.vault;
+
+    fixture.migrate_account(&vault).await.unwrap();
+
+    assert_eq!(fixture.account_len(&vault).await, 8 + Vault::LEN);
+}
+
+#[tokio::test]
+async fn migrate_rejects_other_accounts() {
+    let mut fixture = Fixture::with_vault().await;
+    let pool_state = fixture.pool_state;
+
+    let result = fixture.migrate_account(&pool_state).await;
+
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintOwner);
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::{assert_error, Fixture, DAY, RESERVE_0, RESERVE_1};
+use tool_lp::{Error, PERMANENT_UNLOCK_TIMESTAMP, TOKEN_PER_LP_SCALE};
+
+const DEPOSIT_AMOUNT: u64 = 100_000;
+
+/// A fixture with `DEPOSIT_AMOUNT` locked permanently.
+async fn locked_permanently() -> Fixture {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let user = fixture.primary_user();
+    fixture.make_lock_permanent(&user).await.unwrap();
+    fixture
+}
+
+#[tokio::test]
+async fn make_lock_permanent_never_unlocks() {
+    let mut fixture = locked_permanently().await;
+
+    let user_lock = fixture.user_lock_account().await;
+    assert!(user_lock.is_permanent);
+    assert_eq!(user_lock.unlock_timestamp, PERMANENT_UNLOCK_TIMESTAMP);
+    assert_eq!(
+        fixture.vault_account().await.total_permanent_locked,
+        DEPOSIT_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn withdraw_from_a_permanent_lock_is_rejected() {
+    let mut fixture = locked_permanently().await;
+    let now = fixture.now().await;
+    fixture.warp_to(now + 365 * DAY).await;
+
+    let result = fixture.withdraw(DEPOSIT_AMOUNT).await;
+
+    assert_error(result, Error::PermanentLock);
+    assert_eq!(fixture.user_lock_account().await.amount, DEPOSIT_AMOUNT);
+}
+
+#[tokio::test]
+async fn claim_lp_fees_withdraws_the_growth() {
+    let mut fixture = locked_permanently().await;
+    // Both tokens double per LP.
+    fixture.set_reserves(RESERVE_0 * 2, RESERVE_1 * 2).await;
+    let user = fixture.primary_user();
+
+    fixture.claim_lp_fees(&user).await.unwrap();
+
+    // Half of the LP is growth: 50_000 LP redeems 1_000_000 token 0 and
+    // 4_000_000 token 1, all of it charged the 20% fee.
+    let (user_0, user_1) = (fixture.user_token_0_account, fixture.user_token_1_account);
+    let (fee_0, fee_1) = (fixture.fee_token_0_account(), fixture.fee_token_1_account());
+    assert_eq!(fixture.token_amount(&user_0).await, 800_000);
+    assert_eq!(fixture.token_amount(&user_1).await, 3_200_000);
+    assert_eq!(fixture.token_amount(&fee_0).await, 200_000);
+    assert_eq!(fixture.token_amount(&fee_1).await, 800_000);
+
+    // The rest is re-based at the current price.
+    let user_lock = fixture.user_lock_account().await;
+    assert_eq!(user_lock.amount, DEPOSIT_AMOUNT / 2);
+    assert_eq!(user_lock.deposit_basis_0, 20 * TOKEN_PER_LP_SCALE);
+    assert_eq!(user_lock.deposit_basis_1, 80 * TOKEN_PER_LP_SCALE);
+    let vault = fixture.vault_account().await;
+    assert_eq!(vault.total_locked, DEPOSIT_AMOUNT / 2);
+    assert_eq!(vault.total_permanent_locked, DEPOSIT_AMOUNT / 2);
+}
+
+#[tokio::test]
+async fn claim_lp_fees_without_growth_is_rejected() {
+    let mut fixture = locked_permanently().await;
+    let user = fixture.primary_user();
+
+    let result = fixture.claim_lp_fees(&user).await;
+
+    assert_error(result, Error::NoFeesToClaim);
+    assert_eq!(fixture.user_lock_account().await.amount, DEPOSIT_AMOUNT);
+}
+
+#[tokio::test]
+async fn claimed_growth_cannot_be_claimed_again() {
+    let mut fixture = locked_permanently().await;
+    fixture.set_reserves(RESERVE_0 * 2, RESERVE_1 * 2).await;
+    let user = fixture.primary_user();
+    fixture.claim_lp_fees(&user).await.unwrap();
+
+    let result = fixture.claim_lp_fees(&user).await;
+
+    assert_error(result, Error::NoFeesToClaim);
+    assert_eq!(fixture.user_lock_account().await.amount, DEPOSIT_AMOUNT / 2);
+}
+
+#[tokio::test]
+async fn claim_lp_fees_from_a_time_lock_is_rejected() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    fixture.set_reserves(RESERVE_0 * 2, RESERVE_1 * 2).await;
+    let user = fixture.primary_user();
+
+    let result = fixture.claim_lp_fees(&user).await;
+
+    assert_error(result, Error::LockNotPermanent);
+}
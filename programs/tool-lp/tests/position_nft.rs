@@ -0,0 +1,177 @@
+mod common;
+
+use common::{assert_error, position_mint, position_token_account, Fixture, DAY, USER_LP_AMOUNT};
+use solana_sdk::signature::Signer;
+use tool_lp::Error;
+
+const DEPOSIT_AMOUNT: u64 = 100_000;
+
+#[tokio::test]
+async fn minting_tokenizes_the_lock() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    assert!(!fixture.user_lock_account().await.is_tokenized());
+
+    let user = fixture.primary_user();
+    fixture.mint_position_nft(&user).await.unwrap();
+
+    let user_lock = fixture.user_lock_account().await;
+    let mint = position_mint(&fixture.user_lock);
+    assert_eq!(user_lock.position_mint, mint);
+    let holder_account = position_token_account(&fixture.user.pubkey(), &mint);
+    assert_eq!(fixture.token_amount(&holder_account).await, 1);
+}
+
+#[tokio::test]
+async fn top_up_keeps_the_position_nft() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let depositor = fixture.primary_user();
+    fixture.mint_position_nft(&depositor).await.unwrap();
+
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+
+    let user_lock = fixture.user_lock_account().await;
+    assert_eq!(user_lock.amount, 2 * DEPOSIT_AMOUNT);
+    assert_eq!(user_lock.position_mint, position_mint(&fixture.user_lock));
+}
+
+#[tokio::test]
+async fn former_holder_cannot_top_up_the_sold_lock() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let depositor = fixture.primary_user();
+    fixture.mint_position_nft(&depositor).await.unwrap();
+    let buyer = fixture.add_user();
+    fixture.transfer_position(&depositor, &buyer).await.unwrap();
+
+    let result = fixture.deposit(DEPOSIT_AMOUNT, unlock_timestamp).await;
+
+    assert_error(result, Error::Unauthorized);
+    assert_eq!(fixture.user_lock_account().await.amount, DEPOSIT_AMOUNT);
+    let user_lp_account = fixture.user_lp_account;
+    assert_eq!(
+        fixture.token_amount(&user_lp_account).await,
+        USER_LP_AMOUNT - DEPOSIT_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn emptying_the_lock_burns_its_position_nft() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let depositor = fixture.primary_user();
+    fixture.mint_position_nft(&depositor).await.unwrap();
+    fixture.warp_to(unlock_timestamp).await;
+    let mint = position_mint(&fixture.user_lock);
+
+    fixture.withdraw(DEPOSIT_AMOUNT / 2).await.unwrap();
+    assert!(fixture.user_lock_account().await.is_tokenized());
+
+    fixture.withdraw(DEPOSIT_AMOUNT / 2).await.unwrap();
+    assert!(!fixture.user_lock_account().await.is_tokenized());
+    assert!(!fixture.account_exists(&mint).await);
+    let holder_account = position_token_account(&fixture.user.pubkey(), &mint);
+    assert_eq!(fixture.token_amount(&holder_account).await, 0);
+}
+
+#[tokio::test]
+async fn former_holder_does_not_own_the_next_deposit() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let depositor = fixture.primary_user();
+    fixture.mint_position_nft(&depositor).await.unwrap();
+    let buyer = fixture.add_user();
+    fixture.transfer_position(&depositor, &buyer).await.unwrap();
+    fixture.warp_to(unlock_timestamp).await;
+
+    // The buyer empties the lock through `depositor`'s lock address.
+    let buyer_of_lock = common::TestUser {
+        user_lock: depositor.user_lock,
+        keypair: buyer.keypair.insecure_clone(),
+        ..buyer
+    };
+    fixture
+        .withdraw_as(&buyer_of_lock, DEPOSIT_AMOUNT)
+        .await
+        .unwrap();
+
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    fixture.warp_to(unlock_timestamp).await;
+
+    let result = fixture.withdraw_as(&buyer_of_lock, DEPOSIT_AMOUNT).await;
+    assert_error(result, Error::Unauthorized);
+    let user_lock = fixture.user_lock_account().await;
+    assert!(!user_lock.is_tokenized());
+    assert_eq!(user_lock.amount, DEPOSIT_AMOUNT);
+    let user_lp_account = fixture.user_lp_account;
+    assert_eq!(
+        fixture.token_amount(&user_lp_account).await,
+        USER_LP_AMOUNT - 2 * DEPOSIT_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn keeper_authorization_lapses_with_a_transfer() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let depositor = fixture.primary_user();
+    fixture.mint_position_nft(&depositor).await.unwrap();
+    fixture.set_keeper_as(&depositor, true, 0).await.unwrap();
+    let buyer = fixture.add_user();
+    let keeper = fixture.add_user();
+    fixture.transfer_position(&depositor, &buyer).await.unwrap();
+    fixture.warp_to(unlock_timestamp).await;
+    let buyer_of_lock = common::TestUser {
+        user_lock: depositor.user_lock,
+        keypair: buyer.keypair.insecure_clone(),
+        ..buyer
+    };
+
+    let result = fixture
+        .withdraw_by(&keeper, &buyer_of_lock, DEPOSIT_AMOUNT)
+        .await;
+    assert_error(result, Error::Unauthorized);
+
+    // Once the buyer authorizes keepers themselves, the keeper may withdraw.
+    fixture
+        .set_keeper_as(&buyer_of_lock, true, 0)
+        .await
+        .unwrap();
+    fixture
+        .withdraw_by(&keeper, &buyer_of_lock, DEPOSIT_AMOUNT)
+        .await
+        .unwrap();
+    assert_eq!(fixture.user_lock_account().await.amount, 0);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use common::{Fixture, DAY, RESERVE_0, RESERVE_1};
+
+const DEPOSIT_AMOUNT: u64 = 100_000;
+
+#[tokio::test]
+async fn quote_matches_the_withdrawal() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    fixture.set_reserves(RESERVE_0 * 2, RESERVE_1 * 2).await;
+    fixture.warp_to(unlock_timestamp).await;
+
+    let quote = fixture.quote_withdraw(DEPOSIT_AMOUNT).await;
+    fixture.withdraw(DEPOSIT_AMOUNT).await.unwrap();
+
+    // Half of what the LP redeems is growth, charged the 20% fee.
+    assert_eq!(quote.raw_token_0_amount, 2_000_000);
+    assert_eq!(quote.raw_token_1_amount, 8_000_000);
+    assert_eq!(quote.fee_0_amount, 200_000);
+    assert_eq!(quote.fee_1_amount, 800_000);
+    let (user_0, user_1) = (fixture.user_token_0_account, fixture.user_token_1_account);
+    let (fee_0, fee_1) = (fixture.fee_token_0_account(), fixture.fee_token_1_account());
+    assert_eq!(
+        fixture.token_amount(&user_0).await,
+        quote.net_token_0_amount
+    );
+    assert_eq!(
+        fixture.token_amount(&user_1).await,
+        quote.net_token_1_amount
+    );
+    assert_eq!(fixture.token_amount(&fee_0).await, quote.fee_0_amount);
+    assert_eq!(fixture.token_amount(&fee_1).await, quote.fee_1_amount);
+}
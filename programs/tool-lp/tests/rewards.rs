@@ -0,0 +1,138 @@
+//! Reward stream accounting on `Vault` and `UserLock`, without running the program.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize};
+use tool_lp::{
+    RewardInfo, UserLock, Vault, PERMANENT_UNLOCK_TIMESTAMP, REWARD_CLAIM_PERIOD,
+    REWARD_PER_WEIGHT_SCALE,
+};
+
+const EMISSIONS_PER_SECOND: u64 = 10;
+const START: i64 = 1_000;
+const END: i64 = 1_100;
+
+/// A vault with one stream paying `EMISSIONS_PER_SECOND` from `START` to `END`.
+fn vault_with_reward() -> Vault {
+    let mut vault = Vault::deserialize(&mut &[0; Vault::LEN][..]).unwrap();
+    vault.initialize(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        255,
+        255,
+    );
+    vault.rewards[0] = stream(START, END);
+    vault.reward_generations[0] = 1;
+    vault
+}
+
+fn stream(start_timestamp: i64, end_timestamp: i64) -> RewardInfo {
+    RewardInfo {
+        mint: Pubkey::new_unique(),
+        reward_vault: Pubkey::new_unique(),
+        emissions_per_second: EMISSIONS_PER_SECOND,
+        start_timestamp,
+        end_timestamp,
+        last_update_timestamp: start_timestamp,
+        reward_per_weight: 0,
+    }
+}
+
+/// A permanent lock, whose weight never decays.
+fn user_lock(weight: u64) -> UserLock {
+    let mut user_lock = UserLock::deserialize(&mut &[0; UserLock::LEN][..]).unwrap();
+    user_lock.amount = weight;
+    user_lock.unlock_timestamp = PERMANENT_UNLOCK_TIMESTAMP;
+    user_lock.weight = weight;
+    user_lock.weight_timestamp = START;
+    user_lock
+}
+
+#[test]
+fn emissions_are_shared_by_weight() {
+    let mut vault = vault_with_reward();
+    vault.total_weight = 4;
+
+    vault.update_rewards(START + 20).unwrap();
+
+    assert_eq!(
+        vault.rewards[0].reward_per_weight,
+        200 * REWARD_PER_WEIGHT_SCALE / 4
+    );
+    assert_eq!(vault.rewards[0].last_update_timestamp, START + 20);
+}
+
+#[test]
+fn emissions_without_weight_extend_the_stream() {
+    let mut vault = vault_with_reward();
+
+    vault.update_rewards(START + 50).unwrap();
+    assert_eq!(vault.rewards[0].reward_per_weight, 0);
+    assert_eq!(vault.rewards[0].end_timestamp, END + 50);
+
+    // The first lock only earns what is emitted once it carries weight.
+    vault.total_weight = 5;
+    vault.update_rewards(START + 60).unwrap();
+
+    assert_eq!(
+        vault.rewards[0].reward_per_weight,
+        100 * REWARD_PER_WEIGHT_SCALE / 5
+    );
+}
+
+#[test]
+fn emissions_without_weight_resume_after_the_stream_end() {
+    let mut vault = vault_with_reward();
+
+    // Nothing was emitted, so all 100 seconds are still to come.
+    vault.update_rewards(END + 50).unwrap();
+    assert_eq!(vault.rewards[0].end_timestamp, END + 150);
+
+    vault.total_weight = 5;
+    vault.update_rewards(END + 60).unwrap();
+    assert_eq!(
+        vault.rewards[0].reward_per_weight,
+        100 * REWARD_PER_WEIGHT_SCALE / 5
+    );
+
+    vault.update_rewards(END + 200).unwrap();
+    assert_eq!(
+        vault.rewards[0].reward_per_weight,
+        1_000 * REWARD_PER_WEIGHT_SCALE / 5
+    );
+    assert_eq!(vault.rewards[0].last_update_timestamp, END + 200);
+}
+
+#[test]
+fn stream_slot_expires_after_the_claim_period() {
+    let reward = stream(START, END);
+
+    assert!(!reward.is_expired(END + REWARD_CLAIM_PERIOD - 1));
+    assert!(reward.is_expired(END + REWARD_CLAIM_PERIOD));
+    assert!(!RewardInfo::default().is_expired(END + REWARD_CLAIM_PERIOD));
+}
+
+#[test]
+fn reused_slot_starts_every_lock_afresh() {
+    let mut vault = vault_with_reward();
+    vault.total_weight = 10;
+    let mut early = user_lock(5);
+    early.reset_reward_debts(&vault).unwrap();
+    let mut idle = user_lock(5);
+    vault.update_rewards(END).unwrap();
+    early.settle_rewards(&mut vault, END).unwrap();
+    assert_eq!(early.rewards_owed[0], 500);
+
+    // The slot gets a new stream before either lock checkpoints again.
+    let now = END + REWARD_CLAIM_PERIOD;
+    vault.update_rewards(now).unwrap();
+    vault.rewards[0] = stream(now, now + 10);
+    vault.reward_generations[0] += 1;
+    vault.update_rewards(now + 10).unwrap();
+    early.settle_rewards(&mut vault, now + 10).unwrap();
+    idle.settle_rewards(&mut vault, now + 10).unwrap();
+
+    assert_eq!(early.rewards_owed[0], 50);
+    assert_eq!(idle.rewards_owed[0], 50);
+    assert_eq!(early.reward_generations, vault.reward_generations);
+}
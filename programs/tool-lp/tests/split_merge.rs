@@ -0,0 +1,148 @@
+mod common;
+
+use anchor_lang::prelude::Pubkey;
+use common::{assert_error, position_mint, Fixture, DAY};
+use solana_sdk::signature::Signer;
+use tool_lp::{Error, TOKEN_PER_LP_SCALE};
+
+const DEPOSIT_AMOUNT: u64 = 100_000;
+const SPLIT_AMOUNT: u64 = 40_000;
+
+#[tokio::test]
+async fn split_keeps_the_basis_and_unlock_time() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let owner = fixture.primary_user();
+    let recipient = fixture.add_user();
+
+    fixture
+        .split_lock(&owner, &recipient, SPLIT_AMOUNT)
+        .await
+        .unwrap();
+
+    let source = fixture.user_lock_account().await;
+    let split_off = fixture.try_user_lock_account(&recipient).await.unwrap();
+    assert_eq!(source.amount, DEPOSIT_AMOUNT - SPLIT_AMOUNT);
+    assert_eq!(split_off.amount, SPLIT_AMOUNT);
+    assert_eq!(split_off.user, recipient.keypair.pubkey());
+    assert_eq!(split_off.unlock_timestamp, unlock_timestamp);
+    assert_eq!(split_off.deposit_basis_0, 10 * TOKEN_PER_LP_SCALE);
+    assert_eq!(split_off.deposit_basis_1, 40 * TOKEN_PER_LP_SCALE);
+    assert_eq!(
+        fixture.vault_account().await.total_weight,
+        source.weight + split_off.weight
+    );
+}
+
+#[tokio::test]
+async fn split_to_the_owner_is_rejected() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let owner = fixture.primary_user();
+
+    let result = fixture.split_lock(&owner, &owner, SPLIT_AMOUNT).await;
+
+    assert_error(result, Error::InvalidInput);
+    assert_eq!(fixture.user_lock_account().await.amount, DEPOSIT_AMOUNT);
+}
+
+#[tokio::test]
+async fn split_renews_an_expired_rollover_term_first() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    fixture.set_rollover(8 * DAY).await.unwrap();
+    fixture.warp_to(unlock_timestamp + 1).await;
+    let owner = fixture.primary_user();
+    let recipient = fixture.add_user();
+
+    fixture
+        .split_lock(&owner, &recipient, SPLIT_AMOUNT)
+        .await
+        .unwrap();
+
+    let split_off = fixture.try_user_lock_account(&recipient).await.unwrap();
+    assert_eq!(split_off.unlock_timestamp, unlock_timestamp + 8 * DAY);
+    assert_eq!(split_off.rollover_duration, 8 * DAY);
+    assert_eq!(
+        fixture.user_lock_account().await.unlock_timestamp,
+        unlock_timestamp + 8 * DAY
+    );
+}
+
+#[tokio::test]
+async fn split_into_an_emptied_lock_drops_its_keeper() {
+    let mut fixture = Fixture::with_vault().await;
+    let now = fixture.now().await;
+    let owner = fixture.primary_user();
+    let recipient = fixture.add_user();
+    fixture
+        .deposit_as(&recipient, DEPOSIT_AMOUNT, now + DAY)
+        .await
+        .unwrap();
+    fixture.set_keeper_as(&recipient, true, 100).await.unwrap();
+    fixture
+        .deposit(DEPOSIT_AMOUNT, now + 2 * DAY)
+        .await
+        .unwrap();
+    fixture.warp_to(now + DAY).await;
+    fixture
+        .withdraw_as(&recipient, DEPOSIT_AMOUNT)
+        .await
+        .unwrap();
+
+    fixture
+        .split_lock(&owner, &recipient, SPLIT_AMOUNT)
+        .await
+        .unwrap();
+
+    let split_off = fixture.try_user_lock_account(&recipient).await.unwrap();
+    assert_eq!(split_off.amount, SPLIT_AMOUNT);
+    assert!(!split_off.keeper_enabled);
+    assert_eq!(split_off.keeper_tip_bps, 0);
+    assert_eq!(split_off.keeper_authority, Pubkey::default());
+}
+
+#[tokio::test]
+async fn merge_blends_the_basis_and_takes_the_later_unlock() {
+    let mut fixture = Fixture::with_vault().await;
+    let now = fixture.now().await;
+    let owner = fixture.primary_user();
+    fixture.deposit(DEPOSIT_AMOUNT, now + DAY).await.unwrap();
+    // The second lock is bought at a 20% higher price, through its position NFT.
+    fixture.set_reserves(12_000_000, 48_000_000).await;
+    let seller = fixture.add_user();
+    fixture
+        .deposit_as(&seller, DEPOSIT_AMOUNT, now + 2 * DAY)
+        .await
+        .unwrap();
+    fixture.mint_position_nft(&seller).await.unwrap();
+    fixture.transfer_position(&seller, &owner).await.unwrap();
+    let mint = position_mint(&seller.user_lock);
+    let bought_lock = common::TestUser {
+        user_lock: seller.user_lock,
+        keypair: owner.keypair.insecure_clone(),
+        ..seller
+    };
+
+    fixture.merge_locks(&bought_lock, &owner).await.unwrap();
+
+    assert!(!fixture.account_exists(&bought_lock.user_lock).await);
+    assert!(!fixture.account_exists(&mint).await);
+    let merged = fixture.user_lock_account().await;
+    assert_eq!(merged.amount, 2 * DEPOSIT_AMOUNT);
+    assert_eq!(merged.unlock_timestamp, now + 2 * DAY);
+    assert_eq!(merged.deposit_basis_0, 11 * TOKEN_PER_LP_SCALE);
+    assert_eq!(merged.deposit_basis_1, 44 * TOKEN_PER_LP_SCALE);
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{assert_error, Fixture, DAY};
+use solana_sdk::signature::Signer;
+use tool_lp::Error;
+
+const DEPOSIT_AMOUNT: u64 = 100_000;
+
+#[tokio::test]
+async fn transfer_moves_the_lock_to_the_new_owner() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let before = fixture.user_lock_account().await;
+    let owner = fixture.primary_user();
+    let new_owner = fixture.add_user();
+
+    fixture.transfer_lock(&owner, &new_owner).await.unwrap();
+
+    assert!(!fixture.account_exists(&owner.user_lock).await);
+    let moved = fixture.try_user_lock_account(&new_owner).await.unwrap();
+    assert_eq!(moved.user, new_owner.keypair.pubkey());
+    assert_eq!(moved.amount, DEPOSIT_AMOUNT);
+    assert_eq!(moved.unlock_timestamp, unlock_timestamp);
+    assert_eq!(moved.deposit_basis_0, before.deposit_basis_0);
+    assert_eq!(moved.deposit_basis_1, before.deposit_basis_1);
+
+    fixture.warp_to(unlock_timestamp).await;
+    fixture
+        .withdraw_as(&new_owner, DEPOSIT_AMOUNT)
+        .await
+        .unwrap();
+    let moved = fixture.try_user_lock_account(&new_owner).await.unwrap();
+    assert_eq!(moved.amount, 0);
+}
+
+#[tokio::test]
+async fn tokenized_lock_is_not_transferred() {
+    let mut fixture = Fixture::with_vault().await;
+    let unlock_timestamp = fixture.now().await + DAY;
+    fixture
+        .deposit(DEPOSIT_AMOUNT, unlock_timestamp)
+        .await
+        .unwrap();
+    let owner = fixture.primary_user();
+    fixture.mint_position_nft(&owner).await.unwrap();
+    let new_owner = fixture.add_user();
+
+    let result = fixture.transfer_lock(&owner, &new_owner).await;
+
+    assert_error(result, Error::AlreadyTokenized);
+    assert_eq!(fixture.user_lock_account().await.amount, DEPOSIT_AMOUNT);
+    assert!(fixture.try_user_lock_account(&new_owner).await.is_none());
+}
@@ -0,0 +1,100 @@
+//! Vote checkpoints on `UserLock`, without running the program.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize};
+use tool_lp::{UserLock, Vault, PROPOSAL_VOTING_PERIOD};
+
+const SNAPSHOT: i64 = 100_000;
+const UNLOCK: i64 = SNAPSHOT + 1_000_000;
+
+fn vault() -> Vault {
+    let mut vault = Vault::deserialize(&mut &[0; Vault::LEN][..]).unwrap();
+    vault.initialize(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        255,
+        255,
+    );
+    vault
+}
+
+/// A lock that received `amount` at `timestamp`.
+fn user_lock(vault: &mut Vault, amount: u64, timestamp: i64) -> UserLock {
+    let mut user_lock = UserLock::deserialize(&mut &[0; UserLock::LEN][..]).unwrap();
+    user_lock
+        .credit(vault, amount, UNLOCK, 0, 0, timestamp)
+        .unwrap();
+    user_lock
+}
+
+#[test]
+fn lock_votes_with_what_it_held_at_the_snapshot() {
+    let mut vault = vault();
+    let user_lock = user_lock(&mut vault, 1_000, SNAPSHOT - 10);
+
+    assert_eq!(user_lock.votes_at(SNAPSHOT), 1_000);
+    assert_eq!(user_lock.votes_at(SNAPSHOT - 10), 0);
+}
+
+#[test]
+fn top_up_after_the_snapshot_keeps_the_earlier_votes() {
+    let mut vault = vault();
+    let mut user_lock = user_lock(&mut vault, 1_000, SNAPSHOT - 10);
+
+    // Anyone may top up a lock; the deposit must not cost it its votes.
+    user_lock
+        .credit(&mut vault, 5, UNLOCK, 0, 0, SNAPSHOT + 10)
+        .unwrap();
+    assert_eq!(user_lock.votes_at(SNAPSHOT), 1_000);
+
+    user_lock
+        .credit(
+            &mut vault,
+            5,
+            UNLOCK,
+            0,
+            0,
+            SNAPSHOT + PROPOSAL_VOTING_PERIOD - 1,
+        )
+        .unwrap();
+    assert_eq!(user_lock.votes_at(SNAPSHOT), 1_000);
+}
+
+#[test]
+fn top_up_long_after_the_last_deposit_keeps_the_earlier_votes() {
+    let mut vault = vault();
+    let mut user_lock = user_lock(&mut vault, 1_000, SNAPSHOT - 2 * PROPOSAL_VOTING_PERIOD);
+
+    user_lock
+        .credit(&mut vault, 5, UNLOCK, 0, 0, SNAPSHOT + 10)
+        .unwrap();
+
+    assert_eq!(user_lock.votes_at(SNAPSHOT), 1_000);
+}
+
+#[test]
+fn lp_added_after_the_snapshot_does_not_vote() {
+    let mut vault = vault();
+    let mut user_lock = user_lock(&mut vault, 1_000, SNAPSHOT - 10);
+
+    user_lock
+        .credit(&mut vault, 1_000_000, UNLOCK, 0, 0, SNAPSHOT + 10)
+        .unwrap();
+
+    assert_eq!(user_lock.votes_at(SNAPSHOT), 1_000);
+}
+
+#[test]
+fn lp_removed_after_the_snapshot_does_not_vote() {
+    let mut vault = vault();
+    let mut user_lock = user_lock(&mut vault, 1_000, SNAPSHOT - 10);
+    user_lock
+        .credit(&mut vault, 5, UNLOCK, 0, 0, SNAPSHOT + 10)
+        .unwrap();
+
+    user_lock.amount = 400;
+    user_lock.checkpoint_votes(SNAPSHOT + 20);
+
+    assert_eq!(user_lock.votes_at(SNAPSHOT), 400);
+}
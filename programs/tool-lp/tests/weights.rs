@@ -0,0 +1,104 @@
+//! Lock weight accounting on `Vault` and `UserLock`, without running the program.
+
+use anchor_lang::{prelude::Pubkey, AnchorDeserialize};
+use tool_lp::{
+    LockPolicy, RewardInfo, UserLock, Vault, DEFAULT_MAX_LOCK_DURATION, PERMANENT_UNLOCK_TIMESTAMP,
+    REWARD_PER_WEIGHT_SCALE,
+};
+
+const AMOUNT: u64 = 1_000;
+const NOW: i64 = 1_000_000;
+
+fn vault() -> Vault {
+    let mut vault = Vault::deserialize(&mut &[0; Vault::LEN][..]).unwrap();
+    vault.initialize(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        255,
+        255,
+    );
+    vault
+}
+
+fn user_lock(unlock_timestamp: i64) -> UserLock {
+    let mut user_lock = UserLock::deserialize(&mut &[0; UserLock::LEN][..]).unwrap();
+    user_lock.amount = AMOUNT;
+    user_lock.unlock_timestamp = unlock_timestamp;
+    user_lock
+}
+
+fn policy(max_duration: i64) -> LockPolicy {
+    LockPolicy {
+        max_duration,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn weight_duration_follows_the_policy_without_weight() {
+    let mut vault = vault();
+    assert_eq!(vault.weight_duration, DEFAULT_MAX_LOCK_DURATION);
+
+    vault.set_lock_policy(policy(100));
+
+    assert_eq!(vault.weight_duration, 100);
+}
+
+#[test]
+fn weight_duration_is_kept_while_locks_carry_weight() {
+    let mut vault = vault();
+    vault.set_lock_policy(policy(100));
+    let mut user_lock = user_lock(NOW + 50);
+    user_lock.refresh_weight(&mut vault, NOW).unwrap();
+    assert_eq!(user_lock.weight, AMOUNT / 2);
+
+    vault.set_lock_policy(policy(200));
+
+    assert_eq!(vault.lock_policy.max_duration, 200);
+    assert_eq!(vault.weight_duration, 100);
+    assert_eq!(user_lock.current_weight(&vault, NOW).unwrap(), AMOUNT / 2);
+}
+
+#[test]
+fn stale_weight_is_paid_at_its_average_and_the_rest_shared_again() {
+    let mut vault = vault();
+    vault.set_lock_policy(policy(100));
+    vault.rewards[0] = RewardInfo {
+        mint: Pubkey::new_unique(),
+        reward_vault: Pubkey::new_unique(),
+        emissions_per_second: 10,
+        start_timestamp: NOW,
+        end_timestamp: NOW + 400,
+        last_update_timestamp: NOW,
+        reward_per_weight: 0,
+    };
+    vault.reward_generations[0] = 1;
+    let mut expiring = user_lock(NOW + 100);
+    expiring.refresh_weight(&mut vault, NOW).unwrap();
+    expiring.reset_reward_debts(&vault).unwrap();
+    assert_eq!(expiring.weight, AMOUNT);
+
+    // Its stored weight earns all 2000 emitted, but it decayed to zero halfway.
+    vault.update_rewards(NOW + 200).unwrap();
+    expiring.settle_rewards(&mut vault, NOW + 200).unwrap();
+
+    assert_eq!(expiring.rewards_owed[0], 500);
+    assert_eq!(vault.undistributed_rewards[0], 1_500);
+
+    expiring.refresh_weight(&mut vault, NOW + 200).unwrap();
+    let mut permanent = user_lock(PERMANENT_UNLOCK_TIMESTAMP);
+    permanent.refresh_weight(&mut vault, NOW + 200).unwrap();
+    permanent.reset_reward_debts(&vault).unwrap();
+    let reward_per_weight = vault.rewards[0].reward_per_weight;
+    vault.update_rewards(NOW + 300).unwrap();
+    permanent.settle_rewards(&mut vault, NOW + 300).unwrap();
+
+    assert_eq!(vault.undistributed_rewards[0], 0);
+    assert_eq!(
+        vault.rewards[0].reward_per_weight - reward_per_weight,
+        2_500 * REWARD_PER_WEIGHT_SCALE / AMOUNT as u128
+    );
+    assert_eq!(permanent.rewards_owed[0], 2_500);
+}